@@ -1,8 +1,9 @@
 
 use std::cmp::Ordering;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::hash::Hash;
 use std::fmt::{Debug, Display, Formatter};
+use std::io::{self, Write};
 
 
 
@@ -28,6 +29,31 @@ pub trait State : Display + Clone + Eq + Hash {
     /// MUST be one of those returned by avail_moves() or we risk a panic.
     fn enact_move(&self, mv: &Self::TMove) -> Self;
 
+    /// Returns the cost of performing one of the moves. The default is 1 for every move, so
+    /// the search minimizes the number of moves; override it for puzzles where some moves
+    /// cost more than others. When this is overridden, min_moves_to_win() should estimate
+    /// the total cost rather than the number of moves.
+    fn move_cost(&self, _mv: &Self::TMove) -> usize {
+        1
+    }
+
+    /// Returns the state that is used to decide whether we have already visited this one.
+    /// Some puzzles have symmetries (eg: swapping two identical pieces) where many
+    /// different states are effectively the same; overriding this to return a single
+    /// representative of all those states lets the search treat them as one. The default
+    /// is no symmetry at all.
+    ///
+    /// Two states may ONLY share a key if they are truly interchangeable: whatever can be
+    /// done from one (via avail_moves() and enact_move()) can be mirrored from the other,
+    /// leading to states that again share keys, with the same move_cost() each time. That
+    /// means both are winning or neither is, and the cheapest cost to win from them is the
+    /// same, and so is min_moves_to_win(). The search only ever explores one state per key,
+    /// so if this doesn't hold it can miss the best solution. Calling canonical_key() on
+    /// a key must return the key itself.
+    fn canonical_key(&self) -> Self {
+        self.clone()
+    }
+
     /// For display purposes, prints to stdout the information about howe we are doing in our
     /// A* search. Is really more of a helper function for display rather than part of the State
     /// trait, but we declare it here so that implementors of the trait can override this.
@@ -69,13 +95,15 @@ impl<TS: State> StateToConsider<TS> {
         &self.state
     }
 
-    fn sort_score(&self) -> usize {
-        let move_count = match self.prev {
+    fn move_count(&self) -> usize {
+        match self.prev {
             None => 0,
             Some((_,_,count)) => count
-        };
-        let answer = move_count + self.state.min_moves_to_win();
-        answer
+        }
+    }
+
+    fn sort_score(&self) -> usize {
+        self.move_count() + self.state.min_moves_to_win()
     }
 }
 
@@ -91,26 +119,106 @@ impl<TS: State> Debug for StateToConsider<TS> {
 /// Uses A* to find a minimal solution starting from the given initial_state. Returns the
 /// list of moves (or None if there isn't a solution).
 ///
+/// This stops as soon as it generates a winning state, which finds the fewest moves when
+/// every move costs the same. For states that override move_cost(), use
+/// solve_with_weighted_astar() instead.
+///
 /// print_every_n_moves either 0 (for don't print) or a number (eg: 1000) to print out some
 ///   progress notes every that many moves so we can tell it's still going.
 pub fn solve_with_astar<TS: State>(initial_state: &TS, print_every_n_moves: usize) -> Option<Vec<TS::TMove>> {
+    astar_search(initial_state, print_every_n_moves, None, StopAt::FirstWin, &mut HashMap::new())
+}
+
+
+/// This is the same as solve_with_astar() except that it finds the solution with the lowest
+/// total move_cost(). A winning state found through an expensive move may not be the
+/// cheapest one, so this keeps searching until nothing left in the queue could beat it.
+pub fn solve_with_weighted_astar<TS: State>(initial_state: &TS, print_every_n_moves: usize) -> Option<Vec<TS::TMove>> {
+    astar_search(initial_state, print_every_n_moves, None, StopAt::CheapestWin, &mut HashMap::new())
+}
+
+
+/// Finds the cheapest total move_cost() to every state that can be reached from
+/// initial_state for at most max_cost. Returns a map from the canonical_key() of each of
+/// those states to its cost. This explores everything in reach rather than looking for a
+/// win, so is_winning() and min_moves_to_win() are never used.
+pub fn costs_within<TS: State>(initial_state: &TS, max_cost: usize) -> HashMap<TS, usize> {
+    let mut visited_from: VisitedFrom<TS> = HashMap::new();
+    astar_search(initial_state, 0, None, StopAt::Never{max_cost}, &mut visited_from);
+    visited_from.into_iter()
+        .map(|(state, prev)| (state, prev.map_or(0, |(_, _, cost)| cost)))
+        .collect()
+}
+
+
+/// This is the same as solve_with_astar() except that each state that gets explored is
+/// also recorded into trace (until it hits its limit) so the search can be examined
+/// afterward.
+pub fn solve_with_astar_traced<TS: State>(
+    initial_state: &TS,
+    print_every_n_moves: usize,
+    trace: &mut SearchTrace<TS>
+) -> Option<Vec<TS::TMove>> {
+    astar_search(initial_state, print_every_n_moves, Some(trace), StopAt::FirstWin, &mut HashMap::new())
+}
+
+
+/// Tells astar_search() when it is done.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum StopAt {
+    FirstWin, // as soon as a winning state is generated
+    CheapestWin, // once no queued state could lead to a cheaper win than the best one found
+    Never{max_cost: usize}, // not until everything costing at most max_cost has been explored
+}
+
+
+/// The implementation of solve_with_astar(), solve_with_weighted_astar(), costs_within(),
+/// and solve_with_astar_traced().
+///
+/// visited_from should be passed in empty. When this returns it maps from each state (or
+/// rather, its canonical_key()) which we have considered and explored the neighbors of to
+/// how we got there: (prev_state, prev_move, move_count), or None for the initial_state.
+fn astar_search<TS: State>(
+    initial_state: &TS,
+    print_every_n_moves: usize,
+    mut trace: Option<&mut SearchTrace<TS>>,
+    stop_at: StopAt,
+    visited_from: &mut VisitedFrom<TS>,
+) -> Option<Vec<TS::TMove>> {
     if print_every_n_moves > 0 {
         println!("Starting state: {:}", initial_state);
     }
 
-    // visited_from maps from a state (which we have considered and explored its neighbors) to how
-    // we got there: (prev_state, prev_move, move_count).
-    let mut visited_from: HashMap<TS, Option<(TS, TS::TMove, usize)>> = HashMap::new();
+    // When exploring everything there is no goal to head towards, so the queue is simply
+    //   kept in order of move_count.
+    let score = |x: &StateToConsider<TS>| match stop_at {
+        StopAt::Never{..} => x.move_count(),
+        _ => x.sort_score(),
+    };
 
     // queue is a collection of states we will consider. What we store is
-    //   StateToConsider. The queue is kept sorted by sort_score()
+    //   StateToConsider. The queue is kept sorted by score()
     let mut queue: VecDeque<StateToConsider<TS>> = VecDeque::new();
     queue.push_back(StateToConsider{state: initial_state.clone(), prev: None});
 
+    // best_win is the cheapest winning state found so far: Some(cost, moves, state). We can
+    // stop once nothing left in the queue could possibly be cheaper than it.
+    let mut best_win: Option<(usize, Vec<TS::TMove>, TS)> = None;
+
     let mut loop_ctr: usize = 0;
     loop {
         loop_ctr += 1;
 
+        if let Some((win_cost, _, _)) = &best_win {
+            if queue.front().is_none_or(|x| score(x) >= *win_cost) {
+                let (_, winning_moves, winning_state) = best_win.unwrap();
+                if print_every_n_moves > 0 {
+                    println!("\nSOLVED!! {}", winning_state);
+                }
+                return Some(winning_moves);
+            }
+        }
+
         match queue.pop_front() {
             None => {
                 return None; // we ran out of places to go. Guess it's not solvable!
@@ -121,8 +229,10 @@ pub fn solve_with_astar<TS: State>(initial_state: &TS, print_every_n_moves: usiz
                     Some((_,_, move_count)) => move_count,
                 };
 
+                let state_key = state.canonical_key();
+
                 // What to do if we visited this before?
-                if let Some(prev) = visited_from.get(&state) {
+                if let Some(prev) = visited_from.get(&state_key) {
                     let been_here_same_or_better = match prev {
                         None => true,
                         Some((_visited_state, _grid_move, prev_moves)) => *prev_moves <= move_count, // FIXME: think carefully about off-by-one error
@@ -136,22 +246,25 @@ pub fn solve_with_astar<TS: State>(initial_state: &TS, print_every_n_moves: usiz
 
                 // -- Every so often, print it out so we can monitor progress --
                 if print_every_n_moves > 0 && loop_ctr % print_every_n_moves == 0 {
-                    if print_every_n_moves > 1 || !visited_from.contains_key(&state.clone()) {
-                        state.show_state(loop_ctr, move_count, &visited_from, &queue);
+                    if print_every_n_moves > 1 || !visited_from.contains_key(&state_key) {
+                        state.show_state(loop_ctr, move_count, visited_from, &queue);
                     }
                 }
 
                 // -- mark that we have (or now will!) visited this one --
-                assert!(!visited_from.contains_key(&state)); // Assert that we haven't been here before
-                visited_from.insert(state.clone(), prev);
+                assert!(!visited_from.contains_key(&state_key)); // Assert that we haven't been here before
+                if let Some(trace) = trace.as_deref_mut() {
+                    trace.record(&state, prev.as_ref(), move_count);
+                }
+                visited_from.insert(state_key, prev);
 
                 // -- try each move from here --
                 for mv in state.avail_moves() {
                     let next_state: TS = state.enact_move(&mv);
-                    let next_moves = move_count + 1;
+                    let next_moves = move_count + state.move_cost(&mv);
 
                     // -- maybe we've already been to this one --
-                    let earlier_visit = visited_from.get(&next_state);
+                    let earlier_visit = visited_from.get(&next_state.canonical_key());
                     // -- decide whether to put next_state onto the queue... --
                     let try_next_state = match earlier_visit {
                         None => true, // never seen it, certainly want to try it out
@@ -165,30 +278,38 @@ pub fn solve_with_astar<TS: State>(initial_state: &TS, print_every_n_moves: usiz
                         }
                     };
 
-                    if try_next_state {
-                        if next_state.is_winning() {
-                            if print_every_n_moves > 0 {
-                                println!("\nSOLVED!! {}", next_state);
+                    // -- when exploring everything, only go as far as max_cost --
+                    let exploring = match stop_at {
+                        StopAt::Never{max_cost} => {
+                            if next_moves > max_cost {
+                                continue;
                             }
-                            let winning_moves = Some({
-                                let mut moves: Vec<TS::TMove> = Vec::new();
-                                moves.push(mv.clone());
-                                let mut state_var: &TS = &state;
-                                while let Some((prev_state, prev_move, _)) = visited_from.get(&state_var).unwrap() {
-                                    moves.push((*prev_move).clone());
-                                    state_var = prev_state;
+                            true
+                        },
+                        _ => false,
+                    };
+
+                    if try_next_state {
+                        if next_state.is_winning() && !exploring {
+                            // -- keep it unless we already have one at least as cheap --
+                            if best_win.as_ref().is_none_or(|(win_cost, _, _)| next_moves < *win_cost) {
+                                let mut winning_moves = moves_to(visited_from, &state);
+                                winning_moves.push(mv.clone());
+                                if stop_at == StopAt::FirstWin {
+                                    if print_every_n_moves > 0 {
+                                        println!("\nSOLVED!! {}", next_state);
+                                    }
+                                    return Some(winning_moves);
                                 }
-                                moves.reverse();
-                                moves
-                            });
-                            return winning_moves
+                                best_win = Some((next_moves, winning_moves, next_state));
+                            }
                         } else {
                             // -- Actually add this to the queue --
                             let to_insert = StateToConsider{
                                 state: next_state,
-                                prev: Some((state.clone(), mv.clone(), move_count + 1))
+                                prev: Some((state.clone(), mv.clone(), next_moves))
                             };
-                            let insert_idx = queue.partition_point(|x| x.sort_score() < to_insert.sort_score());
+                            let insert_idx = queue.partition_point(|x| score(x) < score(&to_insert));
                             queue.insert(insert_idx, to_insert);
                         }
                     }
@@ -200,256 +321,528 @@ pub fn solve_with_astar<TS: State>(initial_state: &TS, print_every_n_moves: usiz
 
 
 
-/// A module that specializes astar for the case where we're dealing with things in a grid.
-pub mod grid {
-    use std::fmt::{Debug, Display, Formatter};
-    use std::hash::Hash;
-    use itertools::Itertools;
 
 
-    pub type Coord = (usize, usize);
+/// One state that was explored during a search, as recorded in a SearchTrace.
+pub struct TraceNode<TS: State> {
+    pub state: TS,
+    pub parent: Option<usize>, // index (in the SearchTrace) of the state we came from, if recorded
+    pub mv: Option<TS::TMove>, // the move we took to get here; None for the starting state
+    pub move_count: usize,
+    pub min_moves_to_win: usize,
+}
+
+/// This records the states explored by solve_with_astar_traced(), in the order they were
+/// explored, so that we can look at WHY the search went where it did. Since searches can
+/// explore millions of states, it stops recording once it reaches max_nodes.
+pub struct SearchTrace<TS: State> {
+    max_nodes: usize,
+    nodes: Vec<TraceNode<TS>>,
+    ids: HashMap<TS, usize>, // maps canonical_key() to index in nodes
+    dropped: usize,
+}
+
 
-    #[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
-    pub struct GridMove {
-        from: Coord,
-        dir: Direction,
+impl<TS: State> SearchTrace<TS> {
+    /// Creates a new, empty SearchTrace that will record at most max_nodes states.
+    pub fn new(max_nodes: usize) -> Self {
+        SearchTrace{max_nodes, nodes: Vec::new(), ids: HashMap::new(), dropped: 0}
     }
 
-    #[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
-    pub enum Direction {
-        Up, Down, Left, Right
+    /// Returns the states recorded so far, in the order they were explored.
+    pub fn nodes(&self) -> &[TraceNode<TS>] {
+        &self.nodes
     }
 
-    /// This is a data structure for storing items indexed by a Coord. It provide O(1) lookup
-    /// and also provides Eq and Hash.
-    #[derive(Clone, Debug, Eq, PartialEq, Hash)]
-    pub struct GridVec<T: Eq + Hash + Clone> {
-        size: Coord,
-        data: Vec<T>,
+    /// Returns the number of states that were explored but NOT recorded because the
+    /// trace was already full.
+    pub fn dropped(&self) -> usize {
+        self.dropped
     }
 
-    /// This is used just to return an iterator of Coords.
-    struct GridVecCoordIter {
-        size: Coord,
-        next_val: Option<Coord>,
+    /// Records a state as it is explored.
+    fn record(&mut self, state: &TS, prev: Option<&(TS, TS::TMove, usize)>, move_count: usize) {
+        if self.nodes.len() >= self.max_nodes {
+            self.dropped += 1;
+            return;
+        }
+        let parent = prev.and_then(|(prev_state, _, _)| self.ids.get(&prev_state.canonical_key()).copied());
+        let mv = prev.map(|(_, mv, _)| mv.clone());
+        self.ids.insert(state.canonical_key(), self.nodes.len());
+        self.nodes.push(TraceNode{
+            state: state.clone(),
+            parent,
+            mv,
+            move_count,
+            min_moves_to_win: state.min_moves_to_win(),
+        });
     }
+}
 
 
-    impl Direction {
-        /// Returns the opposite of this direction
-        fn inverse(&self) -> Self {
-            match self {
-                Direction::Up => Direction::Down,
-                Direction::Down => Direction::Up,
-                Direction::Left => Direction::Right,
-                Direction::Right => Direction::Left,
+impl<TS: State> SearchTrace<TS> where TS::TMove: Debug {
+    /// Writes the recorded search tree in Graphviz DOT format. Each node is labeled with
+    /// the order it was explored, its state, and g (moves so far) + h (estimated moves to go).
+    pub fn write_dot<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "digraph search {{")?;
+        writeln!(out, "    node [shape=box, fontname=monospace];")?;
+        for (id, node) in self.nodes.iter().enumerate() {
+            let label = format!("#{}\n{}\ng={} h={}\n", id, node.state, node.move_count, node.min_moves_to_win);
+            writeln!(out, "    n{} [label=\"{}\"];", id, dot_escape(&label))?;
+        }
+        for (id, node) in self.nodes.iter().enumerate() {
+            if let (Some(parent), Some(mv)) = (node.parent, &node.mv) {
+                writeln!(out, "    n{} -> n{} [label=\"{}\"];", parent, id, dot_escape(&format!("{:?}", mv)))?;
             }
         }
+        if self.dropped > 0 {
+            writeln!(out, "    // {} more states were explored but not recorded", self.dropped)?;
+        }
+        writeln!(out, "}}")
+    }
 
-        /// Useful sometimes for rendering a GridMove.
-        pub fn to_ascii_picture(&self) -> char {
-            match self {
-                Direction::Down => 'v',
-                Direction::Up => '^',
-                Direction::Left => '<',
-                Direction::Right => '>',
-            }
+    /// Writes the recorded search tree as JSON lines: one object per explored state with
+    /// fields "id", "parent", "move", "state", "g" and "h".
+    pub fn write_json_lines<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        for (id, node) in self.nodes.iter().enumerate() {
+            let record = json::object!{
+                id: id,
+                parent: node.parent,
+                move: node.mv.as_ref().map(|mv| format!("{:?}", mv)),
+                state: node.state.to_string(),
+                g: node.move_count,
+                h: node.min_moves_to_win,
+            };
+            writeln!(out, "{}", record.dump())?;
         }
+        Ok(())
     }
+}
 
 
-    impl GridMove {
-        /// Useful sometimes for rendering a GridMove.
-        pub fn direction_to_ascii_picture(&self) -> char {
-            self.dir.to_ascii_picture()
-        }
+/// Escapes a string so it can be used inside double quotes in a DOT file. Newlines in
+/// the string become (left-justified) line breaks in the label.
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\l")
+}
 
-        /// Useful sometimes for rendering a GridMove.
-        pub fn direction_rev_to_ascii_picture(&self) -> char {
-            self.dir.inverse().to_ascii_picture()
-        }
+
+/// The map used to record how we reached each state: from the state (or rather, its
+/// canonical_key()) to Some(prev_state, prev_move, move_count) or None for the start.
+type VisitedFrom<TS> = HashMap<TS, Option<(TS, <TS as State>::TMove, usize)>>;
+
+
+/// Follows the chain in visited_from back from state to the start, returning the moves
+/// (in order) that lead from the start to state.
+fn moves_to<TS: State>(visited_from: &VisitedFrom<TS>, state: &TS) -> Vec<TS::TMove> {
+    let mut moves: Vec<TS::TMove> = Vec::new();
+    let mut state_var: &TS = state;
+    while let Some((prev_state, prev_move, _)) = visited_from.get(&state_var.canonical_key()).unwrap() {
+        moves.push(prev_move.clone());
+        state_var = prev_state;
     }
+    moves.reverse();
+    moves
+}
 
 
-    impl<T: Eq + Hash + Clone> IntoIterator for GridVec<T> {
-        type Item = T;
-        type IntoIter = std::vec::IntoIter<T>;
+/// This is a multi-threaded version of solve_with_astar(). It returns a solution with the
+/// same (minimal) number of moves, although if there are several equally short solutions
+/// it may not pick the same one.
+///
+/// It works by grouping the queued states by move_count + min_moves_to_win(), then
+/// repeatedly taking ALL the states that share the lowest score and splitting them
+/// among num_threads threads which call avail_moves() and enact_move() on them. The
+/// results are merged back into the visited set by a single thread, so this pays off when
+/// generating states is expensive compared to hashing them.
+///
+/// print_every_n_moves either 0 (for don't print) or a number (eg: 1000) to print out some
+///   progress notes every that many states examined so we can tell it's still going.
+pub fn solve_with_parallel_astar<TS>(
+    initial_state: &TS,
+    num_threads: usize,
+    print_every_n_moves: usize
+) -> Option<Vec<TS::TMove>>
+    where TS: State + Send + Sync, TS::TMove: Send
+{
+    assert!(num_threads >= 1);
+    if print_every_n_moves > 0 {
+        println!("Starting state: {:}", initial_state);
+    }
 
-        fn into_iter(self) -> Self::IntoIter {
-            self.data.into_iter()
+    // best_counts maps from the canonical_key() of every state we have queued to the fewest
+    // moves we have found to get there. Unlike solve_with_astar(), states are recorded here
+    // when they are queued, and updated if we find a shorter way there.
+    let mut best_counts: HashMap<TS, usize> = HashMap::new();
+    best_counts.insert(initial_state.canonical_key(), 0);
+
+    // came_from maps from every state we have queued to how we got there: (prev_state,
+    // prev_move, move_count). It is keyed by the state itself, NOT by canonical_key(),
+    // because a shorter way to a key may arrive at a different state than the one we
+    // already explored, and the moves to one of them would not lead to the other.
+    let mut came_from: VisitedFrom<TS> = HashMap::new();
+    came_from.insert(initial_state.clone(), None);
+
+    // buckets maps from a sort score to the (state, move_count) pairs that have that score.
+    let mut buckets: BTreeMap<usize, Vec<(TS, usize)>> = BTreeMap::new();
+    buckets.insert(initial_state.min_moves_to_win(), vec![(initial_state.clone(), 0)]);
+
+    let mut examined_ctr: usize = 0;
+    while let Some((score, candidates)) = buckets.pop_first() {
+        // -- skip any that we have since found a shorter way to --
+        let round: Vec<(TS, usize)> = candidates.into_iter()
+            .filter(|(state, move_count)| best_counts[&state.canonical_key()] == *move_count)
+            .collect();
+        if round.is_empty() {
+            continue;
         }
-    }
 
-    /// This converts from an iterator of (Coord,T) into a GridVec<T>. The current version
-    /// will panic if there isn't exactly one value for each Coord.
-    impl<T: Eq + Hash + Clone + Debug> FromIterator<(Coord, T)> for GridVec<T> {
-        fn from_iter<U: IntoIterator<Item=(Coord, T)>>(iter: U) -> Self {
-            let staging: Vec<(Coord, T)> = iter.into_iter().collect_vec();
-            let max_x = staging.iter().map(|(c,_)| c.0).max().unwrap_or(0);
-            let max_y = staging.iter().map(|(c,_)| c.1).max().unwrap_or(0);
-            let size: Coord = (max_x + 1, max_y + 1);
+        // -- Every so often, print it out so we can monitor progress --
+        if print_every_n_moves > 0 && examined_ctr / print_every_n_moves != (examined_ctr + round.len()) / print_every_n_moves {
+            println!(
+                "\nAt {} examining {} states with a score of {}. Have visited {} states and have {} scores queued.",
+                examined_ctr,
+                round.len(),
+                score,
+                best_counts.len(),
+                buckets.len()
+            );
+        }
+        examined_ctr += round.len();
+
+        // -- if any of these wins, nothing else can do better --
+        if let Some((winning_state, _)) = round.iter().find(|(state, _)| state.is_winning()) {
+            if print_every_n_moves > 0 {
+                println!("\nSOLVED!! {}", winning_state);
+            }
+            let mut moves: Vec<TS::TMove> = Vec::new();
+            let mut state_var: &TS = winning_state;
+            while let Some((prev_state, prev_move, _)) = &came_from[state_var] {
+                moves.push(prev_move.clone());
+                state_var = prev_state;
+            }
+            moves.reverse();
+            return Some(moves);
+        }
 
-            let get_value = |idx: usize| {
-                let coord = (idx % size.0, idx / size.0);
-                for (c,v) in &staging {
-                    if *c == coord {
-                        return v.clone()
+        // -- expand all of them in parallel --
+        let chunk_size = round.len().div_ceil(num_threads);
+        let expansions: Vec<(TS, TS::TMove, &TS, usize)> = std::thread::scope(|scope| {
+            let handles: Vec<_> = round.chunks(chunk_size)
+                .map(|chunk| scope.spawn(move || {
+                    let mut found = Vec::new();
+                    for (state, move_count) in chunk {
+                        for mv in state.avail_moves() {
+                            let next_state = state.enact_move(&mv);
+                            let next_moves = move_count + state.move_cost(&mv);
+                            found.push((next_state, mv, state, next_moves));
+                        }
                     }
-                }
-                panic!("No value provided for coordinate {:?}", coord)
-            };
+                    found
+                }))
+                .collect();
+            handles.into_iter()
+                .flat_map(|handle| handle.join().expect("search thread panicked"))
+                .collect()
+        });
+
+        // -- merge the results back in, keeping only ones that are new or improved --
+        for (next_state, mv, state, next_moves) in expansions {
+            let next_key = next_state.canonical_key();
+            let try_next_state = best_counts.get(&next_key)
+                .is_none_or(|earlier_move_count| next_moves < *earlier_move_count);
+            if try_next_state {
+                let next_score = next_moves + next_state.min_moves_to_win();
+                best_counts.insert(next_key, next_moves);
+                came_from.insert(next_state.clone(), Some((state.clone(), mv, next_moves)));
+                buckets.entry(next_score).or_default().push((next_state, next_moves));
+            }
+        }
+    }
+    None // we ran out of places to go. Guess it's not solvable!
+}
+
 
-            let indexes = 0..(size.0 * size.1);
-            let data: Vec<T> = indexes.map(get_value).collect();
 
-            GridVec{size, data}
+// ========================== TESTS ==========================
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Two identical tokens on a line; a move shifts one of them by one step. Because
+    /// the tokens are identical, (a,b) and (b,a) are the same position.
+    #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+    struct TwoTokens(i32, i32);
+
+    impl Display for TwoTokens {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "<{},{}>", self.0, self.1)
         }
     }
 
-    impl<T: Eq + Hash + Clone> GridVec<T> {
-        /// Construct from a vec (which must be rectangular and at least 1x1 in size or it panics).
-        pub fn from_vec2d(data_vec: &Vec<Vec<T>>) -> Self {
-            let height = data_vec.len();
-            assert!(height >= 1);
-            let width = data_vec.first().unwrap().len();
-            assert!(width >= 1);
-            let size = (width, height);
-            assert!(data_vec.iter().all(|x| x.len() == width));
+    impl State for TwoTokens {
+        type TMove = (usize, i32);
 
-            let mut data = Vec::with_capacity(width * height);
-            for row in data_vec.iter() {
-                for cell in row.iter() {
-                    data.push(cell.clone());
-                }
-            }
+        fn is_winning(&self) -> bool {
+            self.canonical_key() == TwoTokens(0, 5)
+        }
+
+        fn min_moves_to_win(&self) -> usize {
+            let TwoTokens(a, b) = self.canonical_key();
+            (a.abs_diff(0) + b.abs_diff(5)) as usize
+        }
 
-            GridVec{size, data}
+        fn avail_moves(&self) -> Vec<Self::TMove> {
+            vec![(0, -1), (0, 1), (1, -1), (1, 1)]
         }
 
-        fn coord_to_index(&self, coord: &Coord) -> usize {
-            if coord.0 >= self.size.0 || coord.1 >= self.size.1 {
-                panic!("Coord {:?} is out of bounds.", coord);
+        fn enact_move(&self, mv: &Self::TMove) -> Self {
+            match mv {
+                (0, delta) => TwoTokens(self.0 + delta, self.1),
+                (_, delta) => TwoTokens(self.0, self.1 + delta),
             }
-            coord.1 * self.size.0 + coord.0
         }
 
-        pub fn index_to_coord(&self, idx: usize) -> Coord {
-            (idx % self.size.0, idx / self.size.0)
+        fn canonical_key(&self) -> Self {
+            TwoTokens(self.0.min(self.1), self.0.max(self.1))
         }
+    }
+
+    #[test]
+    fn test_canonical_key_solution() {
+        let start = TwoTokens(7, 2);
+        let moves = solve_with_astar(&start, 0).unwrap();
+        assert_eq!(moves.len(), 4);
+        let end = moves.iter().fold(start, |s, mv| s.enact_move(mv));
+        assert!(end.is_winning());
+    }
+
+    const MAZE: [&str; 5] = [
+        "S..#......",
+        ".#.#.####.",
+        ".#...#....",
+        ".#####.##.",
+        "......#..E",
+    ];
 
-        /// Returns the (x_size,y_size) of the GridVec.
-        pub fn size(&self) -> Coord {
-            self.size
+    /// A position in MAZE.
+    #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+    struct MazePos(usize, usize);
+
+    impl Display for MazePos {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "({},{})", self.0, self.1)
         }
+    }
+
+    impl State for MazePos {
+        type TMove = MazePos;
 
-        /// Iterate through (references to) the items.
-        pub fn iter(&self) -> impl Iterator<Item = &T> {
-            self.data.iter()
+        fn is_winning(&self) -> bool {
+            MAZE[self.1].as_bytes()[self.0] == b'E'
         }
 
-        /// This is used to iterate through the indexes of the coord. It happens to
-        /// loop through x faster than y.
-        pub fn iter_indexes(&self) -> impl Iterator<Item = Coord> {
-            GridVecCoordIter{size: self.size, next_val: Some((0,0))}
+        fn min_moves_to_win(&self) -> usize {
+            self.0.abs_diff(9) + self.1.abs_diff(4)
         }
 
-        /// This returns (in O(1) time) the item in the GridVec at the given coord. If
-        /// the coord is not within size, then this panics.
-        pub fn get(&self, coord: &Coord) -> &T {
-            let idx = self.coord_to_index(coord);
-            self.data.get(idx).unwrap()
+        fn avail_moves(&self) -> Vec<Self::TMove> {
+            let (x, y) = (self.0 as i32, self.1 as i32);
+            [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)].into_iter()
+                .filter(|(x, y)| *x >= 0 && *y >= 0 && *x < 10 && *y < 5)
+                .map(|(x, y)| MazePos(x as usize, y as usize))
+                .filter(|p| MAZE[p.1].as_bytes()[p.0] != b'#')
+                .collect()
         }
 
-        /// This returns (in O(1) time) a mutable reference to the item in the GridVec at the
-        /// given coord. If the coord is not within size then this panics.
-        pub fn get_mut(&mut self, coord: &Coord) -> &mut T {
-            let idx = self.coord_to_index(coord);
-            self.data.get_mut(idx).unwrap()
+        fn enact_move(&self, mv: &Self::TMove) -> Self {
+            mv.clone()
         }
     }
 
-    impl Iterator for GridVecCoordIter {
-        type Item = Coord;
+    #[test]
+    fn test_parallel_matches_serial() {
+        let start = MazePos(0, 0);
+        let serial = solve_with_astar(&start, 0).unwrap();
+        for num_threads in [1, 2, 4] {
+            let parallel = solve_with_parallel_astar(&start, num_threads, 0).unwrap();
+            assert_eq!(parallel.len(), serial.len());
+            assert!(parallel.last().unwrap().is_winning());
+        }
+    }
 
-        fn next(&mut self) -> Option<Self::Item> {
-            let answer = self.next_val;
-            match self.next_val {
-                None => {
-                    self.next_val = Some((0,0));
-                },
-                Some((x, y)) => {
-                    self.next_val = if x + 1 < self.size.0 {
-                        Some((x + 1, y))
-                    } else if y + 1 < self.size.1 {
-                        Some((0, y + 1))
-                    } else {
-                        None
-                    };
-                },
-            }
-            answer
+    #[test]
+    fn test_parallel_with_canonical_key() {
+        let start = TwoTokens(7, 2);
+        let moves = solve_with_parallel_astar(&start, 3, 0).unwrap();
+        assert_eq!(moves.len(), 4);
+        let end = moves.iter().fold(start, |s, mv| s.enact_move(mv));
+        assert!(end.is_winning());
+    }
+
+    /// A small graph where X1 and X2 (and likewise Y1 and Y2, W1 and W2) share a
+    /// canonical_key(). X1 is reached from A by an expensive move, X2 cheaply by way of D,
+    /// and the heuristic overestimates at D so X1 is explored before X2 is found.
+    #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+    enum Node { A, D, X(u8), Y(u8), W(u8) }
+
+    impl Display for Node {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{:?}", self)
         }
     }
 
-    impl GridMove {
-        /// Construct a GridMove from a coordinate and a direction.
-        pub fn new(from: Coord, dir: Direction) -> Self {
-            GridMove{from, dir}
+    impl State for Node {
+        type TMove = Node; // a move is just the node we move to
+
+        fn is_winning(&self) -> bool {
+            matches!(self, Node::W(_))
         }
 
-        /// Returns the place that the move ends up at.
-        pub fn to(&self) -> Coord {
-            match self.dir {
-                Direction::Up => (self.from.0, self.from.1 - 1),
-                Direction::Down => (self.from.0, self.from.1 + 1),
-                Direction::Left => (self.from.0 - 1, self.from.1),
-                Direction::Right => (self.from.0 + 1, self.from.1),
+        fn min_moves_to_win(&self) -> usize {
+            match self {
+                Node::A | Node::W(_) => 0,
+                Node::D => 11,
+                Node::X(_) | Node::Y(_) => 6,
             }
         }
 
-        /// Returns the place that the move starts from.
-        pub fn from(&self) -> Coord {
-            self.from
+        fn avail_moves(&self) -> Vec<Self::TMove> {
+            match self {
+                Node::A => vec![Node::X(1), Node::D],
+                Node::D => vec![Node::X(2)],
+                Node::X(i) => vec![Node::Y(*i)],
+                Node::Y(i) => vec![Node::W(*i)],
+                Node::W(_) => vec![],
+            }
         }
 
-        /// Given a move, this returns a move that goes from the destination to the start.
-        pub fn inverse(&self) -> Self {
-            Self{from: self.to(), dir: self.dir.inverse()}
+        fn enact_move(&self, mv: &Self::TMove) -> Self {
+            *mv
+        }
+
+        fn move_cost(&self, mv: &Self::TMove) -> usize {
+            if *self == Node::A && *mv == Node::X(1) {5} else {1}
+        }
+
+        fn canonical_key(&self) -> Self {
+            match self {
+                Node::X(_) => Node::X(0),
+                Node::Y(_) => Node::Y(0),
+                Node::W(_) => Node::W(0),
+                _ => *self,
+            }
+        }
+    }
+
+    #[test]
+    fn test_parallel_moves_are_replayable() {
+        for num_threads in [1, 2] {
+            let moves = solve_with_parallel_astar(&Node::A, num_threads, 0).unwrap();
+            let end = moves.iter().fold(Node::A, |s, mv| {
+                assert!(s.avail_moves().contains(mv), "{:?} is not a move from {}", mv, s);
+                s.enact_move(mv)
+            });
+            assert!(end.is_winning());
         }
     }
 
-    impl Display for GridMove {
+    /// Two roads to G: directly, for a cost of 10, or by way of M for a cost of 2.
+    #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+    enum Road { S, M, G }
+
+    impl Display for Road {
         fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-            write!(f, "({},{})->({},{})", self.from.0, self.from.1, self.to().0, self.to().1)
+            write!(f, "{:?}", self)
         }
     }
 
-    /// This returns a list of the directions reachable from this coordinate.
-    pub fn neighbor_dirs(coord: Coord, size: Coord) -> Vec<Direction> {
-        let mut answer = Vec::with_capacity(4);
-        if coord.0 > 0 {
-            answer.push(Direction::Left);
+    impl State for Road {
+        type TMove = Road; // a move is just the place we move to
+
+        fn is_winning(&self) -> bool {
+            *self == Road::G
         }
-        if coord.1 > 0 {
-            answer.push(Direction::Up);
+
+        fn min_moves_to_win(&self) -> usize {
+            0
         }
-        if coord.1 + 1 < size.1 {
-            answer.push(Direction::Down);
+
+        fn avail_moves(&self) -> Vec<Self::TMove> {
+            match self {
+                Road::S => vec![Road::G, Road::M],
+                Road::M => vec![Road::G],
+                Road::G => vec![],
+            }
+        }
+
+        fn enact_move(&self, mv: &Self::TMove) -> Self {
+            *mv
         }
-        if coord.0 + 1 < size.0 {
-            answer.push(Direction::Right);
+
+        fn move_cost(&self, mv: &Self::TMove) -> usize {
+            if *self == Road::S && *mv == Road::G {10} else {1}
         }
-        answer
     }
 
-    /// This returns a list of the moves allowed from the given start point, on a grid
-    /// of the given size.
-    pub fn moves_from(start: Coord, size: Coord) -> Vec<GridMove> {
-        neighbor_dirs(start,size).iter().map(|dir| GridMove::new(start, *dir)).collect()
+    #[test]
+    fn test_first_win() {
+        assert_eq!(solve_with_astar(&Road::S, 0), Some(vec![Road::G]));
+    }
+
+    #[test]
+    fn test_costs_within() {
+        assert_eq!(costs_within(&Road::S, 10), HashMap::from([(Road::S, 0), (Road::M, 1), (Road::G, 2)]));
+        assert_eq!(costs_within(&Road::S, 1), HashMap::from([(Road::S, 0), (Road::M, 1)]));
+        let tokens = costs_within(&TwoTokens(1, 0), 1);
+        assert_eq!(tokens.len(), 5); // the start, plus one step either way for either token
+        assert_eq!(tokens[&TwoTokens(0, 1)], 0); // keyed by canonical_key()
+        assert_eq!(tokens[&TwoTokens(-1, 1)], 1);
+    }
+
+    #[test]
+    fn test_weighted_cheapest_win() {
+        assert_eq!(solve_with_weighted_astar(&Road::S, 0), Some(vec![Road::M, Road::G]));
+        assert_eq!(solve_with_weighted_astar(&Road::M, 0), Some(vec![Road::G]));
+    }
+
+    #[test]
+    fn test_trace() {
+        let start = MazePos(0, 0);
+        let mut trace = SearchTrace::new(1000);
+        let moves = solve_with_astar_traced(&start, 0, &mut trace).unwrap();
+        assert_eq!(moves.len(), solve_with_astar(&start, 0).unwrap().len());
+        assert_eq!(trace.dropped(), 0);
+        let nodes = trace.nodes();
+        assert_eq!(nodes[0].state, start);
+        assert!(nodes[0].parent.is_none());
+        for node in &nodes[1..] {
+            let parent = &nodes[node.parent.unwrap()];
+            assert_eq!(&parent.state.enact_move(node.mv.as_ref().unwrap()), &node.state);
+            assert_eq!(parent.move_count + 1, node.move_count);
+        }
+
+        let mut dot = Vec::new();
+        trace.write_dot(&mut dot).unwrap();
+        let dot = String::from_utf8(dot).unwrap();
+        assert!(dot.starts_with("digraph search {"));
+        assert_eq!(dot.matches(" -> ").count(), nodes.len() - 1);
+
+        let mut lines = Vec::new();
+        trace.write_json_lines(&mut lines).unwrap();
+        let lines = String::from_utf8(lines).unwrap();
+        assert_eq!(lines.lines().count(), nodes.len());
+        let first = json::parse(lines.lines().next().unwrap()).unwrap();
+        assert_eq!(first["state"], "(0,0)");
+        assert!(first["parent"].is_null());
     }
 
-    /// Returns the taxicab distance between the two coords.
-    pub fn taxicab_dist(c1: Coord, c2: Coord) -> usize {
-        c1.0.abs_diff(c2.0) + c1.1.abs_diff(c2.1)
+    #[test]
+    fn test_trace_limit() {
+        let mut trace = SearchTrace::new(3);
+        solve_with_astar_traced(&MazePos(0, 0), 0, &mut trace).unwrap();
+        assert_eq!(trace.nodes().len(), 3);
+        assert!(trace.dropped() > 0);
     }
 }
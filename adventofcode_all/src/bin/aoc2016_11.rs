@@ -56,12 +56,11 @@ mod sorted_vec {
 
 
 use sorted_vec::SortedVec;
-use std::collections::HashSet;
 use std::collections::HashMap;
-use std::collections::VecDeque;
 use std::fmt::{Debug, Display, Formatter};
 use std::fs;
 use anyhow::Error;
+use advent_lib::astar;
 
 
 use nom::{
@@ -218,15 +217,11 @@ impl FloorDescription {
 
 type FloorNum = u8;
 
-/// States that are equivalent if you replace one name with another one are identical for
-/// purposes of the problem and we don't need to explore all of them. So our list of
-/// visited states, instead of storing a State, will store this object which represents
-/// a state without respect for which name is which. This depends on (and verifies) an
-/// assumption that each chip has a corresponding generator, and vice versa.
-#[derive(Hash, Eq, PartialEq, Clone)]
-struct StateIgnoringType {
-    elevator: FloorNum,
-    pairs: SortedVec<(FloorNum, FloorNum)>,
+/// A move: the elevator goes to new_elevator carrying the given items (one or two of them).
+#[derive(Debug, Clone)]
+struct ElevatorMove {
+    new_elevator: FloorNum,
+    items: Vec<Item>,
 }
 
 
@@ -296,10 +291,12 @@ impl State {
         true
     }
 
-    /// Returns a new State identical to this, but the given item is removed
+    /// Returns a new State identical to this, but the given items are removed
     /// from the floor with the current elevator and put on the floor
     /// new_elevator, and the elevator is moved there also.
-    fn move_item(&self, new_elevator: FloorNum, moved_items: Vec<&Item>) -> State {
+    fn move_item(&self, mv: &ElevatorMove) -> State {
+        let new_elevator = mv.new_elevator;
+        let moved_items = &mv.items;
         let mut new_data = Vec::new();
         for (i, floor_items) in self.data.iter().enumerate() {
             let floor_num = FloorNum::try_from(i).unwrap();
@@ -314,7 +311,7 @@ impl State {
                     // add item
                     let mut new_items = floor_items.clone();
                     for moved_item in moved_items.iter() {
-                        new_items.push((*moved_item).clone())
+                        new_items.push(moved_item.clone())
                     }
                     new_items
                 },
@@ -327,8 +324,8 @@ impl State {
         State{elevator: new_elevator, data: new_data}
     }
 
-    /// Returns a list of all possible next states (legal or not).
-    fn possible_next_states(&self) -> Vec<State> {
+    /// Returns a list of all possible moves (whether they lead to legal states or not).
+    fn possible_moves(&self) -> Vec<ElevatorMove> {
         let mut answer = Vec::new();
         let floors = FloorNum::try_from(self.data.len()).unwrap();
         let elevator_floor_items: &SortedVec<Item> = self.data.get(usize::from(self.elevator)).unwrap();
@@ -348,24 +345,25 @@ impl State {
             if elevator_floor_items.len() >= 2 {
                 for p1 in 0..(elevator_floor_items.len() - 1) {
                     for p2 in (p1 + 1)..elevator_floor_items.len() {
-                        let new_floor_items = self.move_item(new_floor, vec![
-                            elevator_floor_items.get(p1).unwrap(),
-                            elevator_floor_items.get(p2).unwrap(),
-                        ]);
-                        answer.push(new_floor_items);
+                        answer.push(ElevatorMove{new_elevator: new_floor, items: vec![
+                            elevator_floor_items.get(p1).unwrap().clone(),
+                            elevator_floor_items.get(p2).unwrap().clone(),
+                        ]});
                     }
                 }
             }
             // -- Try just one thing in the elevator --
             for item in elevator_floor_items.iter() {
-                let new_floor_items = self.move_item(new_floor, vec![&item]);
-                answer.push(new_floor_items);
+                answer.push(ElevatorMove{new_elevator: new_floor, items: vec![item.clone()]});
             }
         }
         answer
     }
 
-    fn to_state_ignoring_type(&self) -> StateIgnoringType {
+    /// Returns the (generator floor, microchip floor) of each pair of items, sorted. This
+    /// depends on (and verifies) an assumption that each chip has a corresponding
+    /// generator, and vice versa.
+    fn pair_floors(&self) -> SortedVec<(FloorNum, FloorNum)> {
         let mut pairs = SortedVec::new();
         let mut found_item_floors: HashMap<String, FloorNum> = HashMap::new();
         for (i, floor_data) in self.data.iter().enumerate() {
@@ -375,15 +373,57 @@ impl State {
                     None => {found_item_floors.insert(item.name.clone(), floor_num);},
                     Some(other_floor_num) => {pairs.push(
                         match item.item_type {
-                            ItemType::Generator => (other_floor_num, floor_num),
-                            ItemType::Microchip => (floor_num, other_floor_num),
+                            ItemType::Generator => (floor_num, other_floor_num),
+                            ItemType::Microchip => (other_floor_num, floor_num),
                         }
                     );},
                 }
             }
         }
-        assert!(found_item_floors.len() == 0);
-        StateIgnoringType{elevator: self.elevator, pairs}
+        assert!(found_item_floors.is_empty());
+        pairs
+    }
+}
+
+
+impl astar::State for State {
+    type TMove = ElevatorMove;
+
+    fn is_winning(&self) -> bool {
+        self.winning()
+    }
+
+    /// Each move carries at most 2 items up one floor, so it takes at least half of the
+    /// total distance the items still have to go.
+    fn min_moves_to_win(&self) -> usize {
+        let top_floor = self.data.len() - 1;
+        let total_distance: usize = self.data.iter().enumerate()
+            .map(|(floor_num, floor_items)| (top_floor - floor_num) * floor_items.len())
+            .sum();
+        total_distance.div_ceil(2)
+    }
+
+    fn avail_moves(&self) -> Vec<Self::TMove> {
+        self.possible_moves().into_iter()
+            .filter(|mv| self.move_item(mv).is_legal())
+            .collect()
+    }
+
+    fn enact_move(&self, mv: &Self::TMove) -> Self {
+        self.move_item(mv)
+    }
+
+    /// States that are equivalent if you replace one name with another one are identical
+    /// for purposes of the problem and we don't need to explore all of them. So the key
+    /// renames the pairs "0", "1", "2", ... in order of the floors they are on.
+    fn canonical_key(&self) -> Self {
+        let mut data: Vec<SortedVec<Item>> = self.data.iter().map(|_| SortedVec::new()).collect();
+        for (i, (generator_floor, microchip_floor)) in self.pair_floors().into_iter().enumerate() {
+            let name = i.to_string();
+            data[usize::from(generator_floor)].push(Item{name: name.clone(), item_type: ItemType::Generator});
+            data[usize::from(microchip_floor)].push(Item{name, item_type: ItemType::Microchip});
+        }
+        State{elevator: self.elevator, data}
     }
 }
 
@@ -413,44 +453,13 @@ fn explore_states(initial_state: State) {
         return;
     }
 
-    let mut visited_states: HashSet<StateIgnoringType> = HashSet::new();
-    visited_states.insert(initial_state.to_state_ignoring_type());
-    let mut available_states: VecDeque<(usize, State)> = VecDeque::new(); // (num_steps, state)
-    available_states.push_back((0,initial_state));
-    let mut number_of_steps = 0;
-
-    loop {
-        // -- grab the state that's on the front of the queue --
-        let (steps, from_state) = available_states.pop_front().expect("There is no way to find a solution.");
-
-        // -- print out info about how our breadth-first search is going --
-        match steps - number_of_steps {
-            0 => {}, // same number of steps as last time
-            1 => {
-                println!("Now searching solutions that require {} steps.", steps);
-                number_of_steps = steps;
-            },
-            _ => panic!("Apparently we are not searching a breadth-first search."),
-        }
-
-        // -- loop through possible next steps --
-        for s in from_state.possible_next_states() {
-            if !visited_states.contains(&s.to_state_ignoring_type()) && s.is_legal() {
-                if s.winning() {
-                    println!("**** FOUND A WINNER ****");
-                    println!("In {} steps:", steps + 1);
-                    println!("{}", s);
-                    return;
-                }
-                if PRINT_WORK {
-                    println!("Going {} steps (we've tried {} legal states):", steps + 1, visited_states.len() + 1);
-                    println!("{}", s);
-                }
-                visited_states.insert(s.to_state_ignoring_type());
-                available_states.push_back((steps + 1, s));
-            }
-        }
-    }
+    let print_every_n_moves = if PRINT_WORK {1000} else {0};
+    let moves = astar::solve_with_astar(&initial_state, print_every_n_moves)
+        .expect("There is no way to find a solution.");
+    let final_state = moves.iter().fold(initial_state, |state, mv| state.move_item(mv));
+    println!("**** FOUND A WINNER ****");
+    println!("In {} steps:", moves.len());
+    println!("{}", final_state);
 }
 
 
@@ -487,3 +496,56 @@ fn main() -> Result<(), Error> {
     part_b(&data);
     Ok(())
 }
+
+
+// ==========================================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use advent_lib::astar::State as _;
+
+    const EXAMPLE: &str = "\
+The first floor contains a hydrogen-compatible microchip and a lithium-compatible microchip.
+The second floor contains a hydrogen generator.
+The third floor contains a lithium generator.
+The fourth floor contains nothing relevant.
+";
+
+    fn example_state() -> State {
+        let (rest, floor_descriptions) = FloorDescription::parse_list(EXAMPLE).unwrap();
+        assert_eq!(rest, "");
+        State::from_descriptions(&floor_descriptions)
+    }
+
+    #[test]
+    fn test_canonical_key_ignores_names() {
+        let state = example_state();
+        let swapped = State{
+            elevator: state.elevator,
+            data: state.data.iter().map(|floor_items| {
+                let mut new_items = SortedVec::new();
+                for item in floor_items.iter() {
+                    let name = if item.name == "hydrogen" {"lithium"} else {"hydrogen"};
+                    new_items.push(Item{name: name.to_string(), item_type: item.item_type});
+                }
+                new_items
+            }).collect(),
+        };
+        assert!(state != swapped);
+        assert!(state.canonical_key() == swapped.canonical_key());
+        assert!(state.canonical_key().canonical_key() == state.canonical_key());
+    }
+
+    #[test]
+    fn test_solve_example() {
+        let state = example_state();
+        let moves = astar::solve_with_astar(&state, 0).unwrap();
+        assert_eq!(moves.len(), 11);
+        let final_state = moves.iter().fold(state, |state, mv| {
+            assert!(state.avail_moves().iter().any(|x| x.new_elevator == mv.new_elevator && x.items == mv.items));
+            state.move_item(mv)
+        });
+        assert!(final_state.winning());
+    }
+}
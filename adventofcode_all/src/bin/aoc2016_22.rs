@@ -8,10 +8,8 @@ use std::collections::{HashMap, VecDeque};
 use std::fmt::{Debug, Display, Formatter};
 use std::hash::{Hash, Hasher};
 use std::io::BufRead;
-use advent_lib::astar::{
-    State, StateToConsider, solve_with_astar,
-    grid::{GridVec, GridMove, Coord, Direction, moves_from},
-};
+use advent_lib::astar::{State, StateToConsider, solve_with_parallel_astar};
+use advent_lib::grid::{self, GridMove, Coord, Direction, moves_from};
 
 
 use nom::{
@@ -71,9 +69,9 @@ struct NodeSpace {
 }
 
 /// Returns whether a given move is legal for a given arrangement of nodes.
-fn is_legal(mv: &GridMove, nodes: &GridVec<NodeSpace>) -> bool {
-    let fr = nodes.get(&mv.from());
-    let to = nodes.get(&mv.to());
+fn is_legal(mv: &GridMove, nodes: &grid::Grid<NodeSpace>) -> bool {
+    let fr = nodes.get(mv.from());
+    let to = nodes.get(mv.to());
     fr.used > 0 && to.avail >= fr.used
 }
 
@@ -81,7 +79,7 @@ fn is_legal(mv: &GridMove, nodes: &GridVec<NodeSpace>) -> bool {
 /// A generalized State implementation
 #[derive(Clone, Debug, Eq)]
 struct GenState {
-    nodes: GridVec<NodeSpace>,
+    nodes: grid::Grid<NodeSpace>,
     goal_data_loc: Coord,
     avail_moves: Vec<GridMove>,
 }
@@ -159,10 +157,10 @@ impl GridLoader {
         for node in self.nodes.iter() {
             max_x = max(max_x, node.x);
             max_y = max(max_y, node.y);
-            nodes.insert((node.x, node.y), node.clone());
+            nodes.insert(Coord(node.x, node.y), node.clone());
         }
         assert_eq!( nodes.len(), (max_x + 1) * (max_y + 1) ); // Guarantees we got all of them
-        Grid{nodes, size: (max_x + 1, max_y + 1)}
+        Grid{nodes, size: Coord(max_x + 1, max_y + 1)}
     }
 }
 
@@ -193,12 +191,12 @@ impl Grid {
         let mut count = 0;
         for y1 in 0..self.size.1 {
             for x1 in 0..self.size.0 {
-                let n1: &Node = self.nodes.get(&(x1,y1)).unwrap();
+                let n1: &Node = self.nodes.get(&Coord(x1,y1)).unwrap();
                 if n1.used != 0 {
                     for y2 in 0..self.size.1 {
                         for x2 in 0..self.size.0 {
                             if (x1,y1) != (x2,y2) {
-                                let n2: &Node = self.nodes.get(&(x2,y2)).unwrap();
+                                let n2: &Node = self.nodes.get(&Coord(x2,y2)).unwrap();
                                 if n1.used <= n2.avail {
                                     count += 1;
                                 }
@@ -212,12 +210,12 @@ impl Grid {
     }
 
     fn goal_data_loc(&self) -> Coord {
-        (self.size.0 - 1, 0)
+        Coord(self.size.0 - 1, 0)
     }
 
     /// Given a Grid, this generates the starting State
     fn get_initial_genstate(&self) -> GenState {
-        let nodes: GridVec<NodeSpace> = self.nodes.iter()
+        let nodes: grid::Grid<NodeSpace> = self.nodes.iter()
             .map(|(coord, node)| (*coord, NodeSpace{used: node.used, avail: node.avail})).collect();
         let avail_moves = derive_avail_moves(&nodes);
         GenState{nodes, goal_data_loc: self.goal_data_loc(), avail_moves }
@@ -243,15 +241,15 @@ impl Grid {
 
         // --- values we observed in the data named foo_seen ---
         // --- constraints we will apply named foo_rule ---
-        let goal_data_size_seen = state.nodes.get(&state.goal_data_loc).used;
+        let goal_data_size_seen = state.nodes.get(state.goal_data_loc).used;
         let zero_used_count_seen = state.nodes.iter()
             .filter(|x| x.used == 0)
             .count();
         if zero_used_count_seen != 1 {
             return Err(NotASpecialGridError);
         }
-        let open_space_loc = state.nodes.iter_indexes()
-            .filter(|c| state.nodes.get(c).used == 0)
+        let open_space_loc = state.nodes.bound().range_by_rows()
+            .filter(|c| state.nodes.get(*c).used == 0)
             .nth(0).unwrap();
         let max_nonzero_avail_seen = state.nodes.iter()
             .filter(|x| x.used != 0)
@@ -297,7 +295,7 @@ impl Grid {
                 avail <= max_avail_rule;
 
         let classify = |c: &Coord| -> Result<NodeClassification, NotASpecialGridError> {
-            let node_space: &NodeSpace = state.nodes.get(c);
+            let node_space: &NodeSpace = state.nodes.get(*c);
             if *c == state.goal_data_loc {
                 match node_space {
                     NodeSpace{used, avail} if valid_filler(*used, *avail) => Ok(NodeClassification::Goal),
@@ -316,7 +314,7 @@ impl Grid {
         if PRINT_WHEN_CLASSIFYING {
             println!("goal_data_size_seen = {}", goal_data_size_seen);
             println!("zero_used_count_seen = {}", zero_used_count_seen);
-            println!("open_space_loc = {:?}", open_space_loc);
+            println!("open_space_loc = {:?}", (open_space_loc.0, open_space_loc.1));
             println!("max_nonzero_avail_seen = {}", max_nonzero_avail_seen);
             println!("max_avail_rule = {}", max_avail_rule);
             println!("min_nonzero_size_seen = {}", min_nonzero_size_seen);
@@ -326,7 +324,7 @@ impl Grid {
             println!("max_filler_content_seen = {}", max_filler_content_seen);
             println!("max_filler_content_rule = {}", max_filler_content_rule);
             println!("min_filler_capacity_rule = {}", min_filler_capacity_rule);
-            for c in state.nodes.iter_indexes() {
+            for c in state.nodes.bound().range_by_rows() {
                 if c.0 == 0 {
                     println!(); // newline at the start of each row
                 }
@@ -340,7 +338,7 @@ impl Grid {
             }
             println!(); // newline at the end of the grid
         } else {
-            for c in state.nodes.iter_indexes() {
+            for c in state.nodes.bound().range_by_rows() {
                 classify(&c)?;
             }
         }
@@ -369,7 +367,7 @@ fn diagonals(bound: Coord) -> Vec<Vec<Coord>> {
         let highbound = min(taxi_dist, bound.0);
         let mut diagonal: Vec<Coord> = Vec::new();
         for x in lowbound..=highbound {
-            diagonal.push((x, taxi_dist - x))
+            diagonal.push(Coord(x, taxi_dist - x))
         }
         answer.push(diagonal);
     }
@@ -382,12 +380,10 @@ fn diagonals(bound: Coord) -> Vec<Vec<Coord>> {
 /// It finds them but we really only use it to find the initial
 /// set of moves because most of the time it's more efficient to only tweak the list
 /// of moves slightly since only 2 nodes have changed.
-fn derive_avail_moves(nodes: &GridVec<NodeSpace>) -> Vec<GridMove> {
-    let indexes = 0..(nodes.size().0 * nodes.size().1);
-    indexes.flat_map(|idx| {
-        let coord = nodes.index_to_coord(idx);
-        moves_from(coord, nodes.size()).into_iter()
-            .filter(|mv| is_legal(mv, &nodes))
+fn derive_avail_moves(nodes: &grid::Grid<NodeSpace>) -> Vec<GridMove> {
+    nodes.bound().range_by_rows().flat_map(|coord| {
+        nodes.moves_from(coord).into_iter()
+            .filter(|mv| is_legal(mv, nodes))
     }).collect()
 }
 
@@ -406,10 +402,10 @@ impl GenState {
     /// of the goal data.
     fn taxicab_plus_moving(&self) -> usize {
         let mut answer = 0;
-        let goal_data_size = self.nodes.get(&self.goal_data_loc).used;
+        let goal_data_size = self.nodes.get(self.goal_data_loc).used;
         for diagonal in diagonals(self.goal_data_loc) {
             answer += 1; // have to move the goal data into this diagonal
-            let largest_avail = diagonal.iter().map(|x| self.nodes.get(x).avail).max().unwrap();
+            let largest_avail = diagonal.iter().map(|x| self.nodes.get(*x).avail).max().unwrap();
             if largest_avail < goal_data_size {
                 answer += 1; // have to move something out to make space for it
             }
@@ -424,7 +420,7 @@ impl State for GenState {
 
     /// This indicates whether a state is a "winning" state.
     fn is_winning(&self) -> bool {
-        self.goal_data_loc == (0,0)
+        self.goal_data_loc == Coord(0,0)
     }
 
     /// This is the heuristic used for the A* search algorithm -- it returns an estimate of
@@ -445,12 +441,12 @@ impl State for GenState {
     fn enact_move(&self, mv: &GridMove) -> Self {
         // --- set the nodes ---
         let mut nodes = self.nodes.clone();
-        let from_node = nodes.get_mut(&mv.from());
+        let from_node = nodes.get_mut(mv.from());
         let amt_moved: usize = from_node.used;
         from_node.avail += amt_moved;
         from_node.used -= amt_moved;
         assert_eq!(from_node.used, 0);
-        let to_node = nodes.get_mut(&mv.to());
+        let to_node = nodes.get_mut(mv.to());
         assert!(to_node.avail >= amt_moved);
         to_node.avail -= amt_moved;
         to_node.used += amt_moved;
@@ -469,10 +465,10 @@ impl State for GenState {
             .copied()
             .collect();
         // re-consider everything that enters or leaves move.from or move.to()
-        let moves_out: Vec<GridMove> = moves_from(mv.from(), self.nodes.size()) // moves from the "from" location
+        let moves_out: Vec<GridMove> = moves_from(mv.from(), self.nodes.bound()) // moves from the "from" location
             .into_iter()
             .chain(
-                moves_from(mv.to(), self.nodes.size()).into_iter() // moves from the "to" location
+                moves_from(mv.to(), self.nodes.bound()).into_iter() // moves from the "to" location
                     .filter(|m| m.to() != mv.from()) // except the one going to "from" location; we already got the reverse of that
             ).collect();
         avail_moves.extend(moves_out.iter().filter(|m| is_legal(m, &nodes))); // add the legal "out" movess
@@ -501,10 +497,10 @@ impl SingleSpaceState {
         let goal_data_loc = self.base.goal_data_loc;
         let mut target_locs: Vec<Coord> = Vec::new(); // places open_space might need to go to
         if goal_data_loc.0 > 0 {
-            target_locs.push((goal_data_loc.0 - 1, goal_data_loc.1));
+            target_locs.push(Coord(goal_data_loc.0 - 1, goal_data_loc.1));
         }
         if goal_data_loc.1 > 0 {
-            target_locs.push((goal_data_loc.0, goal_data_loc.1 - 1));
+            target_locs.push(Coord(goal_data_loc.0, goal_data_loc.1 - 1));
         }
         if target_locs.len() == 0 {
             return 0; // we're already sitting at (0,0)
@@ -586,14 +582,14 @@ impl State for SingleSpaceState {
             self.min_moves_to_win(),
             move_count + self.min_moves_to_win()
         );
-        for c in self.base.nodes.iter_indexes() {
+        for c in self.base.nodes.bound().range_by_rows() {
             if c.0 == 0 {
                 println!(); // newline before each line
             }
             let ch = match c {
                 c if c == self.open_space_loc => '.',
                 c if c == self.base.goal_data_loc => 'X',
-                c if self.base.nodes.get(&c).used >= self.min_blocker_content => '#',
+                c if self.base.nodes.get(c).used >= self.min_blocker_content => '#',
                 c if visited_from.contains_key(&self.dummy_with_space_at(c)) => match visited_from.get(&self.dummy_with_space_at(c)).unwrap() {
                     None => '@',
                     Some((_, grid_move, _)) => grid_move.direction_rev_to_ascii_picture(),
@@ -628,7 +624,7 @@ impl PartialEq for GenState {
 impl PartialEq for SingleSpaceState {
     fn eq(&self, other: &Self) -> bool {
         // ONLY the locations of these two spots matter in equality testing
-        self.base.nodes.size().eq(&other.base.nodes.size()) &&
+        self.base.nodes.bound().eq(&other.base.nodes.bound()) &&
             self.open_space_loc.eq(&other.open_space_loc) &&
             self.base.goal_data_loc.eq(&other.base.goal_data_loc)
     }
@@ -646,7 +642,7 @@ impl Hash for GenState {
 impl Hash for SingleSpaceState {
     fn hash<H: Hasher>(&self, state: &mut H) {
         // ONLY the locations of these two spots matter in equality testing
-        self.base.nodes.size().hash(state);
+        self.base.nodes.bound().hash(state);
         self.open_space_loc.hash(state);
         self.base.goal_data_loc.hash(state);
     }
@@ -654,13 +650,13 @@ impl Hash for SingleSpaceState {
 
 impl Display for GenState {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        for c in self.nodes.iter_indexes() {
+        for c in self.nodes.bound().range_by_rows() {
             if c.0 == 0 {
                 writeln!(f)?; // newline before each row
             }
             write!(f, "{:1}{}{:1} ",
                    if c == self.goal_data_loc {'['} else {' '},
-                   self.nodes.get(&c),
+                   self.nodes.get(c),
                    if c == self.goal_data_loc {']'} else {' '},
             )?;
         }
@@ -678,13 +674,13 @@ impl Display for GenState {
 impl Display for SingleSpaceState {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         if VERBOSE_STATE {
-            for c in self.base.nodes.iter_indexes() {
+            for c in self.base.nodes.bound().range_by_rows() {
                 if c.0 == 0 {
                     writeln!(f)?; // newline before each row
                 }
                 let is_space = c == self.open_space_loc;
                 let is_goal = c == self.base.goal_data_loc;
-                let is_wall = self.base.nodes.get(&c).used >= self.min_blocker_content;
+                let is_wall = self.base.nodes.get(c).used >= self.min_blocker_content;
                 let ch = if is_goal {'X'} else if is_space {'.'} else if is_wall {'#'} else {'o'};
                 write!(f, "{}", ch)?;
             }
@@ -695,7 +691,8 @@ impl Display for SingleSpaceState {
             }
             writeln!(f, "]")?;
         } else {
-            write!(f, "SingleSpaceState{{goal:{:?}, space: {:?}}}", self.base.goal_data_loc, self.open_space_loc)?;
+            let (goal, space) = (self.base.goal_data_loc, self.open_space_loc);
+            write!(f, "SingleSpaceState{{goal:{:?}, space: {:?}}}", (goal.0, goal.1), (space.0, space.1))?;
         }
         Ok(())
     }
@@ -704,13 +701,14 @@ impl Display for SingleSpaceState {
 
 
 fn find_winning_moves(grid: &Grid) -> Option<Vec<GridMove>> {
+    let num_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
     match grid.get_initial_singlespacestate() {
         Some(initial_state) => {
-            solve_with_astar(&initial_state, PRINT_EVERY_N_MOVES)
+            solve_with_parallel_astar(&initial_state, num_threads, PRINT_EVERY_N_MOVES)
         },
         None => {
             let initial_state = grid.get_initial_genstate();
-            solve_with_astar(&initial_state, PRINT_EVERY_N_MOVES)
+            solve_with_parallel_astar(&initial_state, num_threads, PRINT_EVERY_N_MOVES)
         }
     }
 }
@@ -758,11 +756,11 @@ fn read_line() -> String {
 fn print_for_play(state: &SingleSpaceState) {
     let open_space_loc = state.open_space_loc;
     let goal_data_loc = state.base.goal_data_loc;
-    for c in state.base.nodes.iter_indexes() {
+    for c in state.base.nodes.bound().range_by_rows() {
         if c.0 == 0 {
             println!(); // newline at the start of each row
         }
-        let ch = match (c, state.base.nodes.get(&c)) {
+        let ch = match (c, state.base.nodes.get(c)) {
             (c,_) if c == goal_data_loc => 'X',
             (c,_) if c == open_space_loc => '.',
             (_, NodeSpace{used, ..}) if *used >= state.min_blocker_content => 'H',
@@ -800,10 +798,10 @@ fn play_manually(grid: &Grid) {
                 let input = read_line();
                 let manual_action: ManualAction = match input.as_str() {
                     "q" => ManualAction::Quit,
-                    "w" => ManualAction::Move(Direction::Up),
-                    "s" => ManualAction::Move(Direction::Down),
-                    "a" => ManualAction::Move(Direction::Left),
-                    "d" => ManualAction::Move(Direction::Right),
+                    "w" => ManualAction::Move(Direction::North),
+                    "s" => ManualAction::Move(Direction::South),
+                    "a" => ManualAction::Move(Direction::West),
+                    "d" => ManualAction::Move(Direction::East),
                     "z" => ManualAction::Undo,
                     other => ManualAction::Echo(other),
                 };
@@ -864,37 +862,37 @@ mod tests {
 
     #[test]
     fn test_diagonals() {
-        let result = diagonals((4,2));
+        let result = diagonals(Coord(4,2));
         assert_eq!(result, vec![
-            vec![(0,0)],
-            vec![(0,1), (1,0)],
-            vec![(0,2), (1,1), (2,0)],
-            vec![(1,2), (2,1), (3,0)],
-            vec![(2,2), (3,1), (4,0)],
-            vec![(3,2), (4,1)],
+            vec![Coord(0,0)],
+            vec![Coord(0,1), Coord(1,0)],
+            vec![Coord(0,2), Coord(1,1), Coord(2,0)],
+            vec![Coord(1,2), Coord(2,1), Coord(3,0)],
+            vec![Coord(2,2), Coord(3,1), Coord(4,0)],
+            vec![Coord(3,2), Coord(4,1)],
         ]);
     }
 
     #[test]
     fn test_read() {
-        let size = (3,2);
+        let size = Coord(3,2);
         let mut nodes: HashMap<Coord,Node> = HashMap::new();
-        nodes.insert((0,0), Node{x: 0, y: 0, size: 6, used: 1, avail: 5});
-        nodes.insert((1,0), Node{x: 1, y: 0, size: 6, used: 2, avail: 4});
-        nodes.insert((2,0), Node{x: 2, y: 0, size: 6, used: 3, avail: 3});
-        nodes.insert((0,1), Node{x: 0, y: 1, size: 5, used: 0, avail: 5});
-        nodes.insert((1,1), Node{x: 1, y: 1, size: 5, used: 4, avail: 1});
-        nodes.insert((2,1), Node{x: 2, y: 1, size: 5, used: 5, avail: 0});
+        nodes.insert(Coord(0,0), Node{x: 0, y: 0, size: 6, used: 1, avail: 5});
+        nodes.insert(Coord(1,0), Node{x: 1, y: 0, size: 6, used: 2, avail: 4});
+        nodes.insert(Coord(2,0), Node{x: 2, y: 0, size: 6, used: 3, avail: 3});
+        nodes.insert(Coord(0,1), Node{x: 0, y: 1, size: 5, used: 0, avail: 5});
+        nodes.insert(Coord(1,1), Node{x: 1, y: 1, size: 5, used: 4, avail: 1});
+        nodes.insert(Coord(2,1), Node{x: 2, y: 1, size: 5, used: 5, avail: 0});
         let grid = Grid{nodes, size};
 
         let genstate = grid.get_initial_genstate();
-        assert_eq!(genstate.goal_data_loc, (2,0));
-        assert_eq!(genstate.nodes.get(&(0,0)).used, 1);
-        assert_eq!(genstate.nodes.get(&(1,0)).used, 2);
-        assert_eq!(genstate.nodes.get(&(2,0)).used, 3);
-        assert_eq!(genstate.nodes.get(&(0,1)).used, 0);
-        assert_eq!(genstate.nodes.get(&(1,1)).used, 4);
-        assert_eq!(genstate.nodes.get(&(2,1)).used, 5);
+        assert_eq!(genstate.goal_data_loc, Coord(2,0));
+        assert_eq!(genstate.nodes.get(Coord(0,0)).used, 1);
+        assert_eq!(genstate.nodes.get(Coord(1,0)).used, 2);
+        assert_eq!(genstate.nodes.get(Coord(2,0)).used, 3);
+        assert_eq!(genstate.nodes.get(Coord(0,1)).used, 0);
+        assert_eq!(genstate.nodes.get(Coord(1,1)).used, 4);
+        assert_eq!(genstate.nodes.get(Coord(2,1)).used, 5);
     }
 
     #[test]
@@ -902,15 +900,15 @@ mod tests {
         /// Builds a SingleSpaceState that's 6x6 with the goal at goal_coord and the
         /// space at space_coord.
         fn build_state(goal_coord: Coord, space_coord: Coord) -> SingleSpaceState {
-            let size = (6,6);
-            assert_ne!(space_coord, (0, size.1 - 1)); // won't work right if the top-right is the space_coord
+            let size = Coord(6,6);
+            assert_ne!(space_coord, Coord(0, size.1 - 1)); // won't work right if the top-right is the space_coord
             let mut nodes: HashMap<Coord,Node> = HashMap::new();
             for y in 0..6 {
                 for x in 0..6 {
-                    if (x,y) == space_coord {
-                        nodes.insert((x,y), Node{x, y, size: 60, used: 0, avail: 60});
+                    if Coord(x,y) == space_coord {
+                        nodes.insert(Coord(x,y), Node{x, y, size: 60, used: 0, avail: 60});
                     } else {
-                        nodes.insert((x,y), Node{x, y, size: 60, used: 50, avail: 10});
+                        nodes.insert(Coord(x,y), Node{x, y, size: 60, used: 50, avail: 10});
                     }
                 }
             }
@@ -922,17 +920,17 @@ mod tests {
 
         let expected_data = vec![
             // ( goal_loc, space_loc, expect_movess )
-            ((3,0), (4,0), 4),
-            ((4,0), (3,0), 0),
-            ((1,0), (0,1), 1),
-            ((2,2), (1,2), 0),
-            ((2,2), (2,1), 0),
-            ((2,2), (1,1), 1),
-            ((2,2), (0,0), 3),
-            ((2,2), (3,2), 2),
-            ((2,2), (2,3), 2),
-            ((2,2), (0,1), 2),
-            ((3,0), (3,1), 2),
+            (Coord(3,0), Coord(4,0), 4),
+            (Coord(4,0), Coord(3,0), 0),
+            (Coord(1,0), Coord(0,1), 1),
+            (Coord(2,2), Coord(1,2), 0),
+            (Coord(2,2), Coord(2,1), 0),
+            (Coord(2,2), Coord(1,1), 1),
+            (Coord(2,2), Coord(0,0), 3),
+            (Coord(2,2), Coord(3,2), 2),
+            (Coord(2,2), Coord(2,3), 2),
+            (Coord(2,2), Coord(0,1), 2),
+            (Coord(3,0), Coord(3,1), 2),
         ];
 
         for (goal_coord, space_coord, expect_moves) in expected_data {
@@ -18,10 +18,8 @@ use nom::{
     multi::many1,
     sequence::terminated,
 };
-use advent_lib::astar::{
-    solve_with_astar, State,
-    grid::{Coord, GridVec, GridMove, taxicab_dist, moves_from}
-};
+use advent_lib::astar::{solve_with_astar, State};
+use advent_lib::grid::{self, Coord, GridMove};
 use traveling_salesman::{Distances, solve_with_brute_force};
 
 
@@ -53,7 +51,7 @@ enum Cell {
 
 #[derive(Debug)]
 struct Grid {
-    nodes: GridVec<Cell>,
+    nodes: grid::Grid<Cell>,
     points: BTreeMap<PointNum,Coord>,
     cached_distances: OnceCell<Distances>,
 }
@@ -105,11 +103,11 @@ impl Grid {
     /// or if it isn't at least 1x1. Also panics if it finds there is a duplicate PointNum or
     /// the PointNum 0 is missing.
     fn from_vec2d(data: Vec<Vec<Cell>>) -> Self {
-        let nodes = GridVec::from_vec2d(&data);
+        let nodes = grid::Grid::try_from(data).expect("grid should be rectangular");
 
         let mut points = BTreeMap::new();
-        for c in nodes.iter_indexes() {
-            let cell = nodes.get(&c);
+        for c in nodes.bound().range_by_rows() {
+            let cell = nodes.get(c);
             match cell {
                 Cell::Point(point_num) => {
                     assert!(!points.contains_key(point_num));
@@ -126,12 +124,12 @@ impl Grid {
 
     /// Returns the dimensions of the grid.
     fn size(&self) -> Coord {
-        self.nodes.size()
+        self.nodes.bound()
     }
 
     /// Returns a boolean indicating if the given location is a wall.
     fn is_wall(&self, coord: Coord) -> bool {
-        match self.nodes.get(&coord) {
+        match self.nodes.get(coord) {
             Cell::Wall => true,
             _ => false,
         }
@@ -229,11 +227,11 @@ impl<'a> State for RobotState<'a> {
     }
 
     fn min_moves_to_win(&self) -> usize {
-        taxicab_dist(self.robot_pos, *self.goal)
+        self.robot_pos.taxicab_dist(*self.goal)
     }
 
     fn avail_moves(&self) -> Vec<Self::TMove> {
-        moves_from(self.robot_pos, self.grid.size()).into_iter()
+        grid::moves_from(self.robot_pos, self.grid.size()).into_iter()
             .filter(|mv| !self.grid.is_wall(mv.to()))
             .collect_vec()
     }
@@ -256,11 +254,11 @@ impl Display for Cell {
 
 impl Display for Grid {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        for c in self.nodes.iter_indexes() {
+        for c in self.nodes.bound().range_by_rows() {
             if c.0 == 0 {
                 writeln!(f)?; // newline before each row
             }
-            write!(f, "{}", self.nodes.get(&c))?;
+            write!(f, "{}", self.nodes.get(c))?;
         }
         writeln!(f)
     }
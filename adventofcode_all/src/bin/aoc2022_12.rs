@@ -6,10 +6,8 @@ use std::fs;
 use std::fmt::{Display, Formatter};
 use anyhow::anyhow;
 use std::collections::HashMap;
-use advent_lib::astar::{
-    solve_with_astar, State,
-    grid::{Coord, GridVec, GridMove, taxicab_dist, moves_from}
-};
+use advent_lib::astar::{solve_with_astar, State};
+use advent_lib::grid::{Coord, Grid, GridMove};
 
 
 // ======= Switches =======
@@ -48,7 +46,7 @@ type Height = char;
 
 #[derive(Debug, Hash, Eq, PartialEq, Clone)]
 struct HeightMap {
-    spots: GridVec<Height>,
+    spots: Grid<Height>,
     start: Coord,
     end: Coord,
 }
@@ -57,8 +55,8 @@ impl HeightMap {
     fn new(input: &InputGrid) -> Result<Self, anyhow::Error> {
         let height = input.values.len();
         let width = input.values.first().unwrap().len();
-        let mut start_opt: Option<(usize,usize)> = None;
-        let mut end_opt: Option<(usize,usize)> = None;
+        let mut start_opt: Option<Coord> = None;
+        let mut end_opt: Option<Coord> = None;
         let mut chars: Vec<Vec<char>> = vec![vec!['.'; width]; height];
         for (y, row) in input.values.iter().enumerate() {
             for (x, ch) in row.iter().enumerate() {
@@ -66,14 +64,14 @@ impl HeightMap {
                     'S' => {
                         match start_opt {
                             Some(_) => return Err(anyhow!("Multiple start locations.")),
-                            None => start_opt = Some((x,y)),
+                            None => start_opt = Some(Coord(x,y)),
                         };
                         'a'
                     }
                     'E' => {
                         match end_opt {
                             Some(_) => return Err(anyhow!("Multiple end locations.")),
-                            None => end_opt = Some((x,y)),
+                            None => end_opt = Some(Coord(x,y)),
                         };
                         'z'
                     }
@@ -85,21 +83,21 @@ impl HeightMap {
         }
         let start = start_opt.ok_or(anyhow!("No starting location."))?;
         let end = end_opt.ok_or(anyhow!("No ending location."))?;
-        let spots = GridVec::from_vec2d(&chars);
+        let spots = Grid::try_from(chars)?;
         Ok(HeightMap{spots, start, end})
     }
 }
 
 
 /// This is a helper used for Display of HeightMap and WanderState. It is passed a
-/// GridVec<Height> and prints it out, except that it is ALSO passed a dictionary
-/// of Coord -> character mappings which override the values in the GridVec.
-fn fmt_letters_with_overlaps(f: &mut Formatter<'_>, grid: &GridVec<Height>, fixed: &HashMap<Coord, char>) -> std::fmt::Result {
-    let width = grid.size().0;
+/// Grid<Height> and prints it out, except that it is ALSO passed a dictionary
+/// of Coord -> character mappings which override the values in the Grid.
+fn fmt_letters_with_overlaps(f: &mut Formatter<'_>, grid: &Grid<Height>, fixed: &HashMap<Coord, char>) -> std::fmt::Result {
+    let width = grid.bound().x();
     for (i, ch) in grid.iter().enumerate() {
         let x = i % width;
         let y = i / width;
-        let display_ch = fixed.get(&(x,y)).unwrap_or(&ch);
+        let display_ch = fixed.get(&Coord(x,y)).unwrap_or(ch);
         write!(f, "{}", display_ch)?;
         if (i + 1) % width == 0 {
             writeln!(f)?;
@@ -161,14 +159,14 @@ impl<'a> State for WanderState<'a> {
     }
 
     fn min_moves_to_win(&self) -> usize {
-        taxicab_dist(self.pos, self.height_map.end)
+        self.pos.taxicab_dist(self.height_map.end)
     }
 
     fn avail_moves(&self) -> Vec<Self::TMove> {
-        moves_from(self.pos, self.height_map.spots.size()).into_iter()
+        self.height_map.spots.moves_from(self.pos).into_iter()
             .filter(|mv| {
-                let from_height = self.height_map.spots.get(&mv.from());
-                let to_height = self.height_map.spots.get(&mv.to());
+                let from_height = self.height_map.spots.get(mv.from());
+                let to_height = self.height_map.spots.get(mv.to());
                 (i64::from(u32::from(*to_height))) - (i64::from(u32::from(*from_height))) <= 1 // not more than 1 step up
             })
             .collect()
@@ -202,20 +200,20 @@ impl<'a> State for FindPathState<'a> {
     type TMove = GridMove;
 
     fn is_winning(&self) -> bool {
-        *self.height_map.spots.get(&self.pos) == 'a'
+        *self.height_map.spots.get(self.pos) == 'a'
     }
 
     fn min_moves_to_win(&self) -> usize {
-        let height = *self.height_map.spots.get(&self.pos);
+        let height = *self.height_map.spots.get(self.pos);
         let delta_height = (height as i64) - ('a' as i64);
         delta_height as usize
     }
 
     fn avail_moves(&self) -> Vec<Self::TMove> {
-        moves_from(self.pos, self.height_map.spots.size()).into_iter()
+        self.height_map.spots.moves_from(self.pos).into_iter()
             .filter(|mv| {
-                let from_height = self.height_map.spots.get(&mv.from());
-                let to_height = self.height_map.spots.get(&mv.to());
+                let from_height = self.height_map.spots.get(mv.from());
+                let to_height = self.height_map.spots.get(mv.to());
                 (i64::from(u32::from(*to_height))) - (i64::from(u32::from(*from_height))) >= -1 // not more than 1 step down
             })
             .collect()
@@ -316,7 +316,8 @@ mod compute {
     pub fn solve(grove: &Grove, goal: Goal) -> Option<Vec<Step>> {
         let initial_state = GroveState{grove, time: 0, loc: Some(grove.start_coord()), goal};
         let print_every_n_moves = 0;
-        astar::solve_with_astar(&initial_state, print_every_n_moves)
+        let num_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        astar::solve_with_parallel_astar(&initial_state, num_threads, print_every_n_moves)
     }
 
 
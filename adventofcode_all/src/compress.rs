@@ -0,0 +1,239 @@
+//! Coordinate compression. When a puzzle has a few objects spread over an enormous range
+//! (cuboids, sensors, a dig plan), only the coordinates where something starts or stops
+//! matter. Between two such boundaries everything behaves the same, so we can number the
+//! boundaries 0, 1, 2, ... and work with a small Grid of cells, then weight each cell by
+//! its real size to get back lengths, areas, or volumes.
+//!
+//! Cells are half-open: cell i runs from boundary i up to (but not including) boundary
+//! i + 1. So to cover an inclusive range lo..=hi, add the boundaries lo and hi + 1.
+
+use std::ops::Range;
+use num::Num;
+use crate::grid::{Coord, Grid};
+use crate::grid3::{Coord3, Grid3};
+
+
+/// Compression along a single axis.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Compression<T> {
+    boundaries: Vec<T>, // sorted, with no duplicates
+}
+
+/// Compression in two dimensions: one Compression for each axis.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Compression2<T> {
+    pub x: Compression<T>,
+    pub y: Compression<T>,
+}
+
+/// Compression in three dimensions: one Compression for each axis.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Compression3<T> {
+    pub x: Compression<T>,
+    pub y: Compression<T>,
+    pub z: Compression<T>,
+}
+
+
+impl<T: Copy + Ord + Num> Compression<T> {
+    /// Creates a Compression from a collection of boundary values (in any order, and with
+    /// duplicates allowed).
+    pub fn new<I: IntoIterator<Item=T>>(values: I) -> Self {
+        let mut boundaries: Vec<T> = values.into_iter().collect();
+        boundaries.sort();
+        boundaries.dedup();
+        Compression{boundaries}
+    }
+
+    /// Returns the number of distinct boundaries.
+    pub fn len(&self) -> usize {
+        self.boundaries.len()
+    }
+
+    /// Returns true if there are no boundaries.
+    pub fn is_empty(&self) -> bool {
+        self.boundaries.is_empty()
+    }
+
+    /// Returns the number of cells (which is one less than the number of boundaries).
+    pub fn num_cells(&self) -> usize {
+        self.boundaries.len().saturating_sub(1)
+    }
+
+    /// Returns the index of the given boundary, or None if value isn't one of the boundaries.
+    pub fn index_of(&self, value: T) -> Option<usize> {
+        self.boundaries.binary_search(&value).ok()
+    }
+
+    /// Returns the boundary value with the given index. Panics if it is out of range.
+    pub fn value_at(&self, index: usize) -> T {
+        self.boundaries[index]
+    }
+
+    /// Returns the index of the cell that contains value, or None if it is before the
+    /// first boundary or at or after the last one.
+    pub fn cell_containing(&self, value: T) -> Option<usize> {
+        let idx = match self.boundaries.binary_search(&value) {
+            Ok(i) => i,
+            Err(i) => i.checked_sub(1)?,
+        };
+        (idx < self.num_cells()).then_some(idx)
+    }
+
+    /// Returns the range of real values covered by the given cell.
+    pub fn cell_range(&self, cell: usize) -> Range<T> {
+        self.boundaries[cell] .. self.boundaries[cell + 1]
+    }
+
+    /// Returns the number of real values covered by the given cell.
+    pub fn cell_size(&self, cell: usize) -> T {
+        self.boundaries[cell + 1] - self.boundaries[cell]
+    }
+
+    /// Returns the range of cells that exactly covers the real range lo..hi. Both lo and
+    /// hi must be boundaries, or this panics.
+    pub fn cells_between(&self, lo: T, hi: T) -> Range<usize> {
+        let lo_idx = self.index_of(lo).expect("lo is not a boundary");
+        let hi_idx = self.index_of(hi).expect("hi is not a boundary");
+        lo_idx .. hi_idx
+    }
+}
+
+
+impl<T: Copy + Ord + Num> Compression2<T> {
+    /// Creates a Compression2 from a collection of (x, y) boundary points.
+    pub fn new<I: IntoIterator<Item=(T, T)>>(points: I) -> Self {
+        let (xs, ys): (Vec<T>, Vec<T>) = points.into_iter().unzip();
+        Compression2{x: Compression::new(xs), y: Compression::new(ys)}
+    }
+
+    /// Returns the bound of a Grid that has one entry per cell.
+    pub fn bound(&self) -> Coord {
+        Coord(self.x.num_cells(), self.y.num_cells())
+    }
+
+    /// Returns the cell containing the given (x, y), or None if it is outside all cells.
+    pub fn cell_containing(&self, (x, y): (T, T)) -> Option<Coord> {
+        Some(Coord(self.x.cell_containing(x)?, self.y.cell_containing(y)?))
+    }
+
+    /// Returns the area of the given cell.
+    pub fn cell_area(&self, cell: Coord) -> T {
+        self.x.cell_size(cell.0) * self.y.cell_size(cell.1)
+    }
+
+    /// Returns the total area of the cells where grid is true. The grid should have the
+    /// bound returned by bound().
+    pub fn weighted_sum(&self, grid: &Grid<bool>) -> T {
+        assert_eq!(grid.bound(), self.bound());
+        self.bound().range_by_rows()
+            .filter(|c| *grid.get(*c))
+            .fold(T::zero(), |sum, c| sum + self.cell_area(c))
+    }
+}
+
+
+impl<T: Copy + Ord + Num> Compression3<T> {
+    /// Creates a Compression3 from a collection of (x, y, z) boundary points.
+    pub fn new<I: IntoIterator<Item=(T, T, T)>>(points: I) -> Self {
+        let mut xs = Vec::new();
+        let mut ys = Vec::new();
+        let mut zs = Vec::new();
+        for (x, y, z) in points {
+            xs.push(x);
+            ys.push(y);
+            zs.push(z);
+        }
+        Compression3{x: Compression::new(xs), y: Compression::new(ys), z: Compression::new(zs)}
+    }
+
+    /// Returns the bound of a Grid3 that has one entry per cell.
+    pub fn bound(&self) -> Coord3 {
+        Coord3(self.x.num_cells(), self.y.num_cells(), self.z.num_cells())
+    }
+
+    /// Returns the cell containing the given (x, y, z), or None if it is outside all cells.
+    pub fn cell_containing(&self, (x, y, z): (T, T, T)) -> Option<Coord3> {
+        Some(Coord3(self.x.cell_containing(x)?, self.y.cell_containing(y)?, self.z.cell_containing(z)?))
+    }
+
+    /// Returns the volume of the given cell.
+    pub fn cell_volume(&self, cell: Coord3) -> T {
+        self.x.cell_size(cell.0) * self.y.cell_size(cell.1) * self.z.cell_size(cell.2)
+    }
+
+    /// Returns the total volume of the cells where grid is true. The grid should have the
+    /// bound returned by bound().
+    pub fn weighted_sum(&self, grid: &Grid3<bool>) -> T {
+        assert_eq!(grid.bound(), self.bound());
+        grid.iter()
+            .filter(|(_, on)| **on)
+            .fold(T::zero(), |sum, (c, _)| sum + self.cell_volume(c))
+    }
+}
+
+
+
+// ========================== TESTS ==========================
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_compression() {
+        let comp = Compression::new([10, -5, 100, 10, 3]);
+        assert_eq!(comp.len(), 4);
+        assert_eq!(comp.num_cells(), 3);
+        assert_eq!(comp.index_of(10), Some(2));
+        assert_eq!(comp.index_of(11), None);
+        assert_eq!(comp.value_at(0), -5);
+        assert_eq!(comp.cell_containing(-6), None);
+        assert_eq!(comp.cell_containing(-5), Some(0));
+        assert_eq!(comp.cell_containing(50), Some(2));
+        assert_eq!(comp.cell_containing(100), None);
+        assert_eq!(comp.cell_size(2), 90);
+        assert_eq!(comp.cells_between(3, 100), 1..3);
+    }
+
+    #[test]
+    fn test_union_area() {
+        // two overlapping rectangles, given as half-open (x_lo, y_lo, x_hi, y_hi)
+        let rects: [(i64, i64, i64, i64); 2] = [(0, 0, 10, 10), (5, 5, 15, 15)];
+        let comp = Compression2::new(rects.iter().flat_map(|r| [(r.0, r.1), (r.2, r.3)]));
+        let mut covered: Grid<bool> = Grid::new_default(comp.bound());
+        for r in rects.iter() {
+            for x in comp.x.cells_between(r.0, r.2) {
+                for y in comp.y.cells_between(r.1, r.3) {
+                    covered.set(Coord(x, y), true);
+                }
+            }
+        }
+        assert_eq!(comp.weighted_sum(&covered), 175);
+    }
+
+    #[test]
+    fn test_reactor_cuboids() {
+        // The small example from Advent of Code 2021 day 22 (with inclusive ranges).
+        let steps: [(bool, (i64, i64), (i64, i64), (i64, i64)); 4] = [
+            (true, (10, 12), (10, 12), (10, 12)),
+            (true, (11, 13), (11, 13), (11, 13)),
+            (false, (9, 11), (9, 11), (9, 11)),
+            (true, (10, 10), (10, 10), (10, 10)),
+        ];
+        let comp = Compression3::new(steps.iter().flat_map(|(_, x, y, z)| {
+            [(x.0, y.0, z.0), (x.1 + 1, y.1 + 1, z.1 + 1)]
+        }));
+        let mut reactor: Grid3<bool> = Grid3::new_default(comp.bound());
+        for (on, x, y, z) in steps.iter() {
+            for cx in comp.x.cells_between(x.0, x.1 + 1) {
+                for cy in comp.y.cells_between(y.0, y.1 + 1) {
+                    for cz in comp.z.cells_between(z.0, z.1 + 1) {
+                        reactor.set(Coord3(cx, cy, cz), *on);
+                    }
+                }
+            }
+        }
+        assert_eq!(comp.weighted_sum(&reactor), 39);
+    }
+}
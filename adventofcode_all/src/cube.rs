@@ -0,0 +1,362 @@
+//! Walking over the surface of a cube that has been unfolded onto a flat map (a "net").
+//! Rather than listing the 11 possible nets in every orientation, this folds the map up:
+//! starting from one face, each neighboring face on the map gets folded 90 degrees over
+//! the shared edge, so we learn which way each face points in 3D. Once every face knows
+//! its orientation, stepping off any edge of any face can be worked out with a bit of
+//! vector arithmetic.
+
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use crate::grid::{Coord, Direction, Grid};
+
+
+/// A vector in 3D. The cube is centered on the origin.
+type Vec3 = [i64; 3];
+
+fn neg(v: Vec3) -> Vec3 {
+    [-v[0], -v[1], -v[2]]
+}
+
+fn dot(a: Vec3, b: Vec3) -> i64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn plus(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn times(v: Vec3, n: i64) -> Vec3 {
+    [v[0] * n, v[1] * n, v[2] * n]
+}
+
+
+/// An error for a map that can't be folded into a cube.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum CubeNetError {
+    /// The number of filled cells isn't 6 times a square number.
+    WrongCellCount(usize),
+    /// The face-sized block starting at this coord is only partly filled.
+    PartialFace(Coord),
+    /// The faces aren't all connected to each other.
+    NotConnected,
+    /// The faces are connected, but folding them up puts two on the same side of the cube.
+    NotANet,
+}
+
+impl Display for CubeNetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CubeNetError::WrongCellCount(n) => write!(f, "{} cells can't make 6 square faces", n),
+            CubeNetError::PartialFace(c) => write!(f, "the face starting at {} is only partly filled", c),
+            CubeNetError::NotConnected => write!(f, "the faces are not all connected"),
+            CubeNetError::NotANet => write!(f, "the faces don't fold into a cube"),
+        }
+    }
+}
+
+impl Error for CubeNetError {}
+
+
+/// One face of the cube: where it is on the map, and which way it points once folded.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+struct Face {
+    origin: Coord, // the top-left cell on the map
+    normal: Vec3, // points out of the cube
+    right: Vec3, // the direction East on the map goes, once folded
+    down: Vec3, // the direction South on the map goes, once folded
+}
+
+impl Face {
+    /// Returns the 3D direction that dir on the map goes in, for this face.
+    fn dir_vec(&self, dir: Direction) -> Vec3 {
+        match dir {
+            Direction::East => self.right,
+            Direction::South => self.down,
+            Direction::West => neg(self.right),
+            Direction::North => neg(self.down),
+        }
+    }
+
+    /// Returns the face that is beside this one in direction dir on the map, after it is
+    /// folded over the shared edge.
+    fn fold(&self, dir: Direction, origin: Coord) -> Face {
+        let normal = self.dir_vec(dir);
+        let (right, down) = match dir {
+            Direction::East => (neg(self.normal), self.down),
+            Direction::West => (self.normal, self.down),
+            Direction::South => (self.right, neg(self.normal)),
+            Direction::North => (self.right, self.normal),
+        };
+        Face{origin, normal, right, down}
+    }
+}
+
+
+/// A map of a cube unfolded flat. Knows how to move around the surface of the cube.
+#[derive(Debug, Clone)]
+pub struct CubeNet {
+    face_size: usize,
+    faces: Vec<Face>,
+    face_at: HashMap<Coord, usize>, // from (map coord / face_size) to index in faces
+}
+
+impl CubeNet {
+    /// Reads a map where spaces are off the cube and any other character is on it (as in
+    /// Advent of Code 2022 day 22). Lines may be of different lengths.
+    pub fn from_char_map(map: &str) -> Result<Self, CubeNetError> {
+        let lines: Vec<&str> = map.lines().collect();
+        let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+        let filled = Grid::from_function(Coord(width, lines.len()), |c| {
+            lines[c.1].chars().nth(c.0).is_some_and(|ch| ch != ' ')
+        });
+        Self::from_filled(&filled)
+    }
+
+    /// Finds the cube net in a grid that is true for the cells that are on the cube.
+    pub fn from_filled(filled: &Grid<bool>) -> Result<Self, CubeNetError> {
+        let count = filled.iter().filter(|b| **b).count();
+        let face_size = (count / 6).isqrt();
+        if count == 0 || face_size * face_size * 6 != count {
+            return Err(CubeNetError::WrongCellCount(count));
+        }
+
+        // --- find the faces ---
+        let bound = filled.bound();
+        let face_bound = Coord(bound.0.div_ceil(face_size), bound.1.div_ceil(face_size));
+        let mut origins: Vec<Coord> = Vec::new();
+        for block in face_bound.range_by_rows() {
+            let origin = Coord(block.0 * face_size, block.1 * face_size);
+            let cells_filled = Coord(face_size, face_size).range_by_rows()
+                .filter(|c| filled.try_get(origin + *c).is_some_and(|b| *b))
+                .count();
+            if cells_filled == face_size * face_size {
+                origins.push(origin);
+            } else if cells_filled > 0 {
+                return Err(CubeNetError::PartialFace(origin));
+            }
+        }
+        let face_at: HashMap<Coord, usize> = origins.iter().enumerate()
+            .map(|(i, c)| (Coord(c.0 / face_size, c.1 / face_size), i))
+            .collect();
+
+        // --- fold them up ---
+        let mut folded: Vec<Option<Face>> = vec![None; origins.len()];
+        folded[0] = Some(Face{origin: origins[0], normal: [0, 0, 1], right: [1, 0, 0], down: [0, 1, 0]});
+        let mut queue = VecDeque::from([0]);
+        while let Some(i) = queue.pop_front() {
+            let face = folded[i].unwrap();
+            let block = Coord(face.origin.0 / face_size, face.origin.1 / face_size);
+            for dir in Direction::ALL {
+                if let Some(&j) = block.step(dir).and_then(|b| face_at.get(&b)) {
+                    if folded[j].is_none() {
+                        folded[j] = Some(face.fold(dir, origins[j]));
+                        queue.push_back(j);
+                    }
+                }
+            }
+        }
+        let faces: Vec<Face> = folded.into_iter().collect::<Option<_>>().ok_or(CubeNetError::NotConnected)?;
+        for (i, face) in faces.iter().enumerate() {
+            if faces[..i].iter().any(|other| other.normal == face.normal) {
+                return Err(CubeNetError::NotANet);
+            }
+        }
+        Ok(CubeNet{face_size, faces, face_at})
+    }
+
+    /// Returns the length of an edge of the cube.
+    pub fn face_size(&self) -> usize {
+        self.face_size
+    }
+
+    /// Returns the top-left corner of each of the 6 faces on the map, in order by rows.
+    pub fn face_origins(&self) -> Vec<Coord> {
+        self.faces.iter().map(|f| f.origin).collect()
+    }
+
+    /// Returns which face (an index into face_origins()) the coord is on, or None if it
+    /// isn't on the cube.
+    pub fn face_of(&self, coord: Coord) -> Option<usize> {
+        self.face_at.get(&Coord(coord.0 / self.face_size, coord.1 / self.face_size)).copied()
+    }
+
+    /// Returns true if the coord is on the cube.
+    pub fn contains(&self, coord: Coord) -> bool {
+        self.face_of(coord).is_some()
+    }
+
+    /// Takes one step from pos in direction dir, going around the edge of the cube if
+    /// needed. Returns the new position and the direction we are now facing (which only
+    /// changes when crossing an edge that wasn't already joined on the map). Panics if
+    /// pos isn't on the cube.
+    pub fn step(&self, pos: Coord, dir: Direction) -> (Coord, Direction) {
+        let face = &self.faces[self.face_of(pos).expect("pos is not on the cube")];
+        let s = self.face_size as i64;
+        let local = (pos.0 as i64 - face.origin.0 as i64, pos.1 as i64 - face.origin.1 as i64);
+        let next = match dir {
+            Direction::East => (local.0 + 1, local.1),
+            Direction::South => (local.0, local.1 + 1),
+            Direction::West => (local.0 - 1, local.1),
+            Direction::North => (local.0, local.1 - 1),
+        };
+        if (0..s).contains(&next.0) && (0..s).contains(&next.1) {
+            let next_pos = Coord(face.origin.0 + next.0 as usize, face.origin.1 + next.1 as usize);
+            return (next_pos, dir);
+        }
+
+        // Positions on the surface are in units of half a cell, so the center of every
+        // cell is a whole number and the cube goes from -s to s on each axis.
+        let center = plus(
+            times(face.normal, s),
+            plus(times(face.right, 2 * local.0 + 1 - s), times(face.down, 2 * local.1 + 1 - s)),
+        );
+        let going = face.dir_vec(dir);
+        let arrived = plus(center, plus(going, neg(face.normal)));
+        let new_face = self.faces.iter().find(|f| f.normal == going).unwrap();
+        let new_x = ((dot(arrived, new_face.right) + s - 1) / 2) as usize;
+        let new_y = ((dot(arrived, new_face.down) + s - 1) / 2) as usize;
+        let new_dir = Direction::ALL.into_iter()
+            .find(|d| new_face.dir_vec(*d) == neg(face.normal))
+            .unwrap();
+        (Coord(new_face.origin.0 + new_x, new_face.origin.1 + new_y), new_dir)
+    }
+
+    /// This is the edge-wrap function: if taking a step from pos in direction dir would go
+    /// off the map, returns where on the cube that step really goes (and the new
+    /// direction). Returns None if the step stays on the map.
+    pub fn wrap(&self, pos: Coord, dir: Direction) -> Option<(Coord, Direction)> {
+        match pos.step(dir) {
+            Some(next) if self.contains(next) => None,
+            _ => Some(self.step(pos, dir)),
+        }
+    }
+}
+
+
+
+// ========================== TESTS ==========================
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The 11 nets of a cube. Each '#' is a face.
+    const ALL_NETS: [&str; 11] = [
+        "#...\n####\n#...\n",
+        "#...\n####\n.#..\n",
+        "#...\n####\n..#.\n",
+        "#...\n####\n...#\n",
+        ".#..\n####\n.#..\n",
+        ".#..\n####\n..#.\n",
+        "##..\n.###\n.#..\n",
+        "##..\n.###\n..#.\n",
+        "##..\n.###\n...#\n",
+        "##..\n.##.\n..##\n",
+        "###..\n..###\n",
+    ];
+
+    /// Makes a map of a net where each face is size by size cells, in all 8 orientations.
+    fn maps_of(net: &str, size: usize) -> Vec<Grid<bool>> {
+        let rows: Vec<&str> = net.lines().collect();
+        let bound = Coord(rows[0].len() * size, rows.len() * size);
+        let map = Grid::from_function(bound, |c| rows[c.1 / size].as_bytes()[c.0 / size] == b'#');
+        let flipped = map.flip_horizontal();
+        vec![
+            map.rotate90(), map.rotate180(), map.rotate270(),
+            flipped.rotate90(), flipped.rotate180(), flipped.rotate270(),
+            map, flipped,
+        ]
+    }
+
+    /// Checks that moving around the cube behaves like a cube should.
+    fn check_walks(cube: &CubeNet, map: &Grid<bool>) {
+        let size = cube.face_size();
+        for (pos, on_cube) in map.iter_with_coords() {
+            if !*on_cube {
+                continue;
+            }
+            for dir in Direction::ALL {
+                // a step can always be undone by turning around and stepping back
+                let (next, next_dir) = cube.step(pos, dir);
+                assert!(cube.contains(next));
+                assert_eq!(cube.step(next, next_dir.reverse()), (pos, dir.reverse()));
+                // going straight for 4 edges' worth of steps comes back around
+                let (mut p, mut d) = (pos, dir);
+                for _ in 0 .. 4 * size {
+                    (p, d) = cube.step(p, d);
+                }
+                assert_eq!((p, d), (pos, dir));
+            }
+        }
+    }
+
+    #[test]
+    fn test_every_net() {
+        for net in ALL_NETS {
+            for size in 1..=4 {
+                for map in maps_of(net, size) {
+                    let cube = CubeNet::from_filled(&map).unwrap();
+                    assert_eq!(cube.face_size(), size);
+                    check_walks(&cube, &map);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_not_nets() {
+        let err = |net: &str| CubeNet::from_filled(&maps_of(net, 2).pop().unwrap()).unwrap_err();
+        assert_eq!(err("###\n###\n"), CubeNetError::NotANet);
+        assert_eq!(err("####\n#..#\n"), CubeNetError::NotANet);
+        assert_eq!(err("###.\n...#\n..##\n"), CubeNetError::NotConnected);
+        assert_eq!(err("####\n#...\n"), CubeNetError::WrongCellCount(20));
+        assert_eq!(
+            CubeNet::from_char_map(" ..\n..\n........\n........\n..\n..").unwrap_err(),
+            CubeNetError::PartialFace(Coord(0, 0)),
+        );
+    }
+
+    #[test]
+    fn test_monkey_map() {
+        // The example from Advent of Code 2022 day 22.
+        let map = [
+            "        ...#",
+            "        .#..",
+            "        #...",
+            "        ....",
+            "...#.......#",
+            "........#...",
+            "..#....#....",
+            "..........#.",
+            "        ...#....",
+            "        .....#..",
+            "        .#......",
+            "        ......#.",
+        ];
+        let cube = CubeNet::from_char_map(&map.join("\n")).unwrap();
+        assert_eq!(cube.face_size(), 4);
+        assert_eq!(cube.wrap(Coord(11, 5), Direction::East), Some((Coord(14, 8), Direction::South)));
+        assert_eq!(cube.wrap(Coord(10, 11), Direction::South), Some((Coord(1, 7), Direction::North)));
+        assert_eq!(cube.wrap(Coord(10, 10), Direction::South), None);
+
+        let is_wall = |c: Coord| map[c.1].as_bytes()[c.0] == b'#';
+        let (mut pos, mut dir) = (Coord(8, 0), Direction::East);
+        for (turn, dist) in [(' ', 10), ('R', 5), ('L', 5), ('R', 10), ('L', 4), ('R', 5), ('L', 5)] {
+            dir = match turn {
+                'R' => dir.clockwise(),
+                'L' => dir.counter_clockwise(),
+                _ => dir,
+            };
+            for _ in 0..dist {
+                let (next, next_dir) = cube.step(pos, dir);
+                if is_wall(next) {
+                    break;
+                }
+                (pos, dir) = (next, next_dir);
+            }
+        }
+        let facing = Direction::ALL.iter().position(|d| *d == dir).unwrap();
+        assert_eq!(1000 * (pos.1 + 1) + 4 * (pos.0 + 1) + facing, 5031);
+    }
+}
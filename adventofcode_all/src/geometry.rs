@@ -0,0 +1,137 @@
+//! Puzzles that trace a loop around the grid (a dig plan, a pipe loop) often want to know
+//! how many cells are inside it. When the loop is huge, filling a Grid won't work, but the
+//! shoelace formula gives the area from the vertices and Pick's theorem turns that into a
+//! count of lattice points. Everything here is generic over the integer type so that i128
+//! can be used when the coordinates get enormous.
+
+use num::{Integer, Signed};
+use crate::grid::Direction;
+
+
+/// Given a list of steps (a direction and a distance) starting from start, returns the
+/// vertices visited: the point after each step. If the steps form a closed loop then the
+/// last vertex will be start again; that is fine to pass to the other functions here. North
+/// is toward smaller y, as in the grid module.
+pub fn vertices_from_steps<N, I>(start: (N, N), steps: I) -> Vec<(N, N)>
+    where N: Integer + Signed + Copy, I: IntoIterator<Item=(Direction, N)>
+{
+    let mut pos = start;
+    steps.into_iter()
+        .map(|(dir, dist)| {
+            pos = match dir {
+                Direction::East => (pos.0 + dist, pos.1),
+                Direction::South => (pos.0, pos.1 + dist),
+                Direction::West => (pos.0 - dist, pos.1),
+                Direction::North => (pos.0, pos.1 - dist),
+            };
+            pos
+        })
+        .collect()
+}
+
+/// Returns the edges of the closed polygon with the given vertices (including the edge
+/// from the last vertex back to the first).
+fn edges<N: Copy>(vertices: &[(N, N)]) -> impl Iterator<Item=((N, N), (N, N))> + '_ {
+    vertices.iter().zip(vertices.iter().cycle().skip(1)).map(|(a, b)| (*a, *b))
+}
+
+/// Returns twice the area of the polygon with the given vertices, using the shoelace
+/// formula. It is always an integer (whereas the area might be a half).
+pub fn twice_area<N>(vertices: &[(N, N)]) -> N
+    where N: Integer + Signed + Copy
+{
+    edges(vertices)
+        .fold(N::zero(), |sum, (a, b)| sum + a.0 * b.1 - b.0 * a.1)
+        .abs()
+}
+
+/// Returns the area of the polygon with the given vertices, rounded down. For a rectilinear
+/// polygon the area is always a whole number.
+pub fn shoelace_area<N>(vertices: &[(N, N)]) -> N
+    where N: Integer + Signed + Copy
+{
+    twice_area(vertices) / (N::one() + N::one())
+}
+
+/// Returns the number of lattice points that lie on the boundary of the polygon with the
+/// given vertices. The edges need not be horizontal or vertical.
+pub fn boundary_points<N>(vertices: &[(N, N)]) -> N
+    where N: Integer + Signed + Copy
+{
+    edges(vertices)
+        .fold(N::zero(), |sum, (a, b)| sum + (b.0 - a.0).abs().gcd(&(b.1 - a.1).abs()))
+}
+
+/// Returns the number of lattice points strictly inside the polygon with the given
+/// vertices, using Pick's theorem (A = I + B/2 - 1).
+pub fn interior_points<N>(vertices: &[(N, N)]) -> N
+    where N: Integer + Signed + Copy
+{
+    let two = N::one() + N::one();
+    (twice_area(vertices) - boundary_points(vertices) + two) / two
+}
+
+/// Returns the number of lattice points inside or on the boundary of the polygon with the
+/// given vertices. If the loop was traced along the centers of grid cells, this is the
+/// number of cells the loop covers (its own cells plus all the ones it encloses).
+pub fn enclosed_cells<N>(vertices: &[(N, N)]) -> N
+    where N: Integer + Signed + Copy
+{
+    interior_points(vertices) + boundary_points(vertices)
+}
+
+
+
+// ========================== TESTS ==========================
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_square() {
+        let square: Vec<(i64, i64)> = vec![(0, 0), (3, 0), (3, 3), (0, 3)];
+        assert_eq!(shoelace_area(&square), 9);
+        assert_eq!(boundary_points(&square), 12);
+        assert_eq!(interior_points(&square), 4);
+        assert_eq!(enclosed_cells(&square), 16);
+        let triangle: Vec<(i64, i64)> = vec![(0, 0), (4, 0), (0, 4)];
+        assert_eq!(twice_area(&triangle), 16);
+        assert_eq!(boundary_points(&triangle), 12);
+        assert_eq!(interior_points(&triangle), 3);
+    }
+
+    /// The example dig plan from Advent of Code 2023 day 18, given as (direction letter,
+    /// distance, hex code).
+    const DIG_PLAN: [(char, i64, &str); 14] = [
+        ('R', 6, "70c710"), ('D', 5, "0dc571"), ('L', 2, "5713f0"), ('D', 2, "d2c081"),
+        ('R', 2, "59c680"), ('D', 2, "411b91"), ('L', 5, "8ceee2"), ('U', 2, "caa173"),
+        ('L', 1, "1b58a2"), ('U', 2, "caa171"), ('R', 2, "7807d2"), ('U', 3, "a77fa3"),
+        ('L', 2, "015232"), ('U', 2, "7a21e3"),
+    ];
+
+    fn dir_from_char(c: char) -> Direction {
+        match c {
+            'R' | '0' => Direction::East,
+            'D' | '1' => Direction::South,
+            'L' | '2' => Direction::West,
+            'U' | '3' => Direction::North,
+            _ => panic!("invalid direction {}", c),
+        }
+    }
+
+    #[test]
+    fn test_dig_plan() {
+        let steps = DIG_PLAN.iter().map(|(c, dist, _)| (dir_from_char(*c), *dist));
+        let vertices = vertices_from_steps((0, 0), steps);
+        assert_eq!(vertices.last(), Some(&(0, 0)));
+        assert_eq!(enclosed_cells(&vertices), 62);
+
+        let big_steps = DIG_PLAN.iter().map(|(_, _, hex)| {
+            let dist = i128::from_str_radix(&hex[..5], 16).unwrap();
+            (dir_from_char(hex.chars().last().unwrap()), dist)
+        });
+        let big_vertices: Vec<(i128, i128)> = vertices_from_steps((0, 0), big_steps);
+        assert_eq!(enclosed_cells(&big_vertices), 952408144115);
+    }
+}
@@ -1,8 +1,20 @@
 /// I build this so often I made a library. It's a grid x/y of things.
 
 pub use coord::Coord;
-pub use direction::Direction;
+pub use icoord::ICoord;
+pub use direction::{Direction, Direction8};
+pub use grid_move::{GridMove, moves_from};
 pub use grid::*;
+pub use transform::SubGrid;
+pub use to_char::{ToChar, InvalidChar, bool_from_char};
+pub use rays::line_between;
+pub use bit_grid::BitGrid;
+pub use diff::{Change, TrackedGrid};
+pub use sparse_grid::SparseGrid;
+pub use pathfinding::{Neighborhood, Walkable};
+pub use regions::{ComponentId, Components};
+pub use timed::PeriodicObstacles;
+pub use tiled::TiledGrid;
 
 
 // ============================================ Direction =============================================
@@ -10,7 +22,7 @@ pub use grid::*;
 mod direction {
     use std::fmt::{Debug, Display, Formatter};
 
-    #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+    #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
     pub enum Direction {
         East, South, West, North
     }
@@ -50,6 +62,16 @@ mod direction {
                 North => South,
             }
         }
+
+        /// Returns an arrow character pointing in this direction. Useful for rendering moves.
+        pub fn to_ascii_picture(&self) -> char {
+            match self {
+                East => '>',
+                South => 'v',
+                West => '<',
+                North => '^',
+            }
+        }
     }
 
     impl Display for Direction {
@@ -62,6 +84,111 @@ mod direction {
             })
         }
     }
+
+
+    /// Like Direction, but also including the diagonals.
+    #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+    pub enum Direction8 {
+        East, SouthEast, South, SouthWest, West, NorthWest, North, NorthEast
+    }
+
+    impl Direction8 {
+        /// All eight directions, going clockwise starting from East.
+        pub const ALL: [Direction8; 8] = [
+            Direction8::East, Direction8::SouthEast, Direction8::South, Direction8::SouthWest,
+            Direction8::West, Direction8::NorthWest, Direction8::North, Direction8::NorthEast,
+        ];
+
+        /// Returns the position of this in ALL.
+        fn index(&self) -> usize {
+            *self as usize
+        }
+
+        /// Returns the next Direction8 clockwise from this one (a 45 degree turn).
+        pub fn clockwise(&self) -> Direction8 {
+            Self::ALL[(self.index() + 1) % 8]
+        }
+
+        /// Returns the next Direction8 counter-clockwise from this one (a 45 degree turn).
+        pub fn counter_clockwise(&self) -> Direction8 {
+            Self::ALL[(self.index() + 7) % 8]
+        }
+
+        /// Returns the Direction8 a 90 degree turn clockwise from this one.
+        pub fn clockwise_90(&self) -> Direction8 {
+            Self::ALL[(self.index() + 2) % 8]
+        }
+
+        /// Returns the Direction8 a 90 degree turn counter-clockwise from this one.
+        pub fn counter_clockwise_90(&self) -> Direction8 {
+            Self::ALL[(self.index() + 6) % 8]
+        }
+
+        /// Returns the opposite Direction8.
+        pub fn reverse(&self) -> Direction8 {
+            Self::ALL[(self.index() + 4) % 8]
+        }
+
+        /// Returns true for the four diagonal directions.
+        pub fn is_diagonal(&self) -> bool {
+            self.index() % 2 == 1
+        }
+
+        /// Returns the (dx, dy) of one step in this direction. North is toward smaller y.
+        pub fn offset(&self) -> (i64, i64) {
+            match self {
+                Direction8::East => (1, 0),
+                Direction8::SouthEast => (1, 1),
+                Direction8::South => (0, 1),
+                Direction8::SouthWest => (-1, 1),
+                Direction8::West => (-1, 0),
+                Direction8::NorthWest => (-1, -1),
+                Direction8::North => (0, -1),
+                Direction8::NorthEast => (1, -1),
+            }
+        }
+    }
+
+    impl From<Direction> for Direction8 {
+        fn from(value: Direction) -> Self {
+            match value {
+                East => Direction8::East,
+                South => Direction8::South,
+                West => Direction8::West,
+                North => Direction8::North,
+            }
+        }
+    }
+
+    /// A Direction8 can become a Direction only if it isn't diagonal.
+    impl TryFrom<Direction8> for Direction {
+        type Error = Direction8;
+
+        fn try_from(value: Direction8) -> Result<Self, Self::Error> {
+            match value {
+                Direction8::East => Ok(East),
+                Direction8::South => Ok(South),
+                Direction8::West => Ok(West),
+                Direction8::North => Ok(North),
+                _ => Err(value),
+            }
+        }
+    }
+
+    impl Display for Direction8 {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", match self {
+                Direction8::East => "E",
+                Direction8::SouthEast => "SE",
+                Direction8::South => "S",
+                Direction8::SouthWest => "SW",
+                Direction8::West => "W",
+                Direction8::NorthWest => "NW",
+                Direction8::North => "N",
+                Direction8::NorthEast => "NE",
+            })
+        }
+    }
 }
 
 // ============================================ Coord =============================================
@@ -71,7 +198,7 @@ mod coord {
     use std::ops::{Add, AddAssign};
     use std::cmp::{Ordering, PartialOrd};
     use std::cmp;
-    use super::Direction;
+    use super::{Direction, Direction8};
 
 
     #[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
@@ -164,6 +291,32 @@ mod coord {
             ]
         }
 
+        /// This gives the coord that is one step away in the given (possibly diagonal)
+        /// direction, or None if stepping that way would take us below 0.
+        pub fn step8(&self, dir: Direction8) -> Option<Coord> {
+            let (dx, dy) = dir.offset();
+            Some(Coord(self.0.checked_add_signed(dx as isize)?, self.1.checked_add_signed(dy as isize)?))
+        }
+
+        /// This gives the coord that is one step away in the given (possibly diagonal)
+        /// direction. But, it returns None instead if the step would take us below 0 or
+        /// beyond the given bound.
+        pub fn bounded_step8(&self, dir: Direction8, bound: Coord) -> Option<Coord> {
+            self.step8(dir).filter(|c| c.0 < bound.0 && c.1 < bound.1)
+        }
+
+        /// This returns all the neighbors of this coord including the diagonals (going
+        /// clockwise from East). It will *not* include any neighbors that would have an x
+        /// or y coordinate < 0, or which would not be < bound.
+        pub fn neighbors8(&self, bound: Coord) -> Vec<Coord> {
+            Direction8::ALL.iter().filter_map(|dir| self.bounded_step8(*dir, bound)).collect()
+        }
+
+        /// Returns the taxicab distance between this coord and another.
+        pub fn taxicab_dist(&self, other: Coord) -> usize {
+            self.0.abs_diff(other.0) + self.1.abs_diff(other.1)
+        }
+
         /// This returns the directions of all adjacent neighbors of this coord. It will *not*
         /// include the direction to any neighbors hat would have an x or y coordinate < 0, or
         /// which would not be < bound.
@@ -344,6 +497,26 @@ mod coord {
             ]);
         }
 
+        #[test]
+        fn test_neighbors8() {
+            assert_eq!(Coord(0,0).neighbors8(Coord(3,3)), vec![Coord(1,0), Coord(1,1), Coord(0,1)]);
+            assert_eq!(Coord(1,1).neighbors8(Coord(3,3)).len(), 8);
+            assert_eq!(Coord(2,1).bounded_step8(Direction8::NorthEast, Coord(3,3)), None);
+            assert_eq!(Coord(2,1).bounded_step8(Direction8::SouthWest, Coord(3,3)), Some(Coord(1,2)));
+        }
+
+        #[test]
+        fn test_direction8_rotation() {
+            assert_eq!(Direction8::North.clockwise(), Direction8::NorthEast);
+            assert_eq!(Direction8::North.clockwise_90(), Direction8::East);
+            assert_eq!(Direction8::East.counter_clockwise(), Direction8::NorthEast);
+            assert_eq!(Direction8::SouthWest.reverse(), Direction8::NorthEast);
+            for dir in Direction::ALL {
+                let dir8: Direction8 = dir.into();
+                assert_eq!(dir8.clockwise_90(), dir.clockwise().into());
+            }
+        }
+
         #[test]
         fn test_iter_size_hint_by_rows() {
             let coord = Coord(3,3);
@@ -370,14 +543,206 @@ mod coord {
 }
 
 
+// ============================================ ICoord =============================================
+
+mod icoord {
+    use std::fmt::{Display, Formatter};
+    use std::ops::{Add, AddAssign, Sub};
+    use super::{Coord, Direction, Direction8};
+
+    /// A coordinate that (unlike Coord) can be negative. Useful when the puzzle has no
+    /// natural edge, such as something wandering around an infinite plane. As with Coord,
+    /// North is toward smaller y.
+    #[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, Default)]
+    pub struct ICoord(pub i64, pub i64);
+
+    impl ICoord {
+        /// Convenient alias for coord.0.
+        pub fn x(&self) -> i64 {
+            self.0
+        }
+
+        /// Convenient alias for coord.1.
+        pub fn y(&self) -> i64 {
+            self.1
+        }
+
+        /// This gives the coord that is one step away in the given direction.
+        pub fn step(&self, dir: Direction) -> ICoord {
+            match dir {
+                Direction::East => ICoord(self.0 + 1, self.1),
+                Direction::South => ICoord(self.0, self.1 + 1),
+                Direction::West => ICoord(self.0 - 1, self.1),
+                Direction::North => ICoord(self.0, self.1 - 1),
+            }
+        }
+
+        /// This returns all the adjacent neighbors of this coord.
+        pub fn neighbors(&self) -> [ICoord; 4] {
+            [
+                ICoord(self.0 - 1, self.1),
+                ICoord(self.0, self.1 - 1),
+                ICoord(self.0 + 1, self.1),
+                ICoord(self.0, self.1 + 1),
+            ]
+        }
+
+        /// This gives the coord that is one step away in the given (possibly diagonal)
+        /// direction.
+        pub fn step8(&self, dir: Direction8) -> ICoord {
+            let (dx, dy) = dir.offset();
+            ICoord(self.0 + dx, self.1 + dy)
+        }
+
+        /// This returns all the neighbors of this coord including the diagonals (going
+        /// clockwise from East).
+        pub fn neighbors8(&self) -> [ICoord; 8] {
+            Direction8::ALL.map(|dir| self.step8(dir))
+        }
+
+        /// Returns the taxicab distance between this coord and another.
+        pub fn taxicab_dist(&self, other: ICoord) -> u64 {
+            self.0.abs_diff(other.0) + self.1.abs_diff(other.1)
+        }
+    }
+
+    impl Display for ICoord {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "({},{})", self.0, self.1)
+        }
+    }
+
+    impl Add for ICoord {
+        type Output = Self;
+
+        fn add(self, rhs: Self) -> Self::Output {
+            ICoord(self.0 + rhs.0, self.1 + rhs.1)
+        }
+    }
+
+    impl AddAssign for ICoord {
+        fn add_assign(&mut self, rhs: Self) {
+            self.0 += rhs.0;
+            self.1 += rhs.1;
+        }
+    }
+
+    impl Sub for ICoord {
+        type Output = Self;
+
+        fn sub(self, rhs: Self) -> Self::Output {
+            ICoord(self.0 - rhs.0, self.1 - rhs.1)
+        }
+    }
+
+    impl From<Coord> for ICoord {
+        fn from(value: Coord) -> Self {
+            ICoord(value.0 as i64, value.1 as i64)
+        }
+    }
+
+    /// An ICoord can become a Coord only if neither value is negative.
+    impl TryFrom<ICoord> for Coord {
+        type Error = std::num::TryFromIntError;
+
+        fn try_from(value: ICoord) -> Result<Self, Self::Error> {
+            Ok(Coord(value.0.try_into()?, value.1.try_into()?))
+        }
+    }
+}
+
+
+// ============================================ GridMove =============================================
+
+mod grid_move {
+    use std::cmp::Ordering;
+    use std::fmt::{Display, Formatter};
+    use super::{Coord, Direction};
+
+    /// A single step from one Coord to an adjacent one. This is handy as the move type
+    /// when doing an A* search over a grid.
+    #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+    pub struct GridMove {
+        from: Coord,
+        dir: Direction,
+    }
+
+    impl GridMove {
+        /// Construct a GridMove from a coordinate and a direction.
+        pub fn new(from: Coord, dir: Direction) -> Self {
+            GridMove{from, dir}
+        }
+
+        /// Returns the place that the move ends up at. Panics if that would be below 0.
+        pub fn to(&self) -> Coord {
+            self.from.safe_step(self.dir)
+        }
+
+        /// Returns the place that the move starts from.
+        pub fn from(&self) -> Coord {
+            self.from
+        }
+
+        /// Returns the direction of the move.
+        pub fn dir(&self) -> Direction {
+            self.dir
+        }
+
+        /// Given a move, this returns a move that goes from the destination to the start.
+        pub fn inverse(&self) -> Self {
+            Self{from: self.to(), dir: self.dir.reverse()}
+        }
+
+        /// Useful sometimes for rendering a GridMove.
+        pub fn direction_to_ascii_picture(&self) -> char {
+            self.dir.to_ascii_picture()
+        }
+
+        /// Useful sometimes for rendering a GridMove.
+        pub fn direction_rev_to_ascii_picture(&self) -> char {
+            self.dir.reverse().to_ascii_picture()
+        }
+    }
+
+    impl Display for GridMove {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}->{}", self.from, self.to())
+        }
+    }
+
+    /// GridMoves are sorted by the x, then y of the starting point, then by direction. (Coord
+    /// itself is only partially ordered, so this can't just be derived.)
+    impl Ord for GridMove {
+        fn cmp(&self, other: &Self) -> Ordering {
+            (self.from.0, self.from.1, self.dir).cmp(&(other.from.0, other.from.1, other.dir))
+        }
+    }
+
+    impl PartialOrd for GridMove {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    /// This returns a list of the moves allowed from the given start point, on a grid
+    /// of the given bound.
+    pub fn moves_from(start: Coord, bound: Coord) -> Vec<GridMove> {
+        start.neighbor_directions(bound).into_iter().map(|dir| GridMove::new(start, dir)).collect()
+    }
+}
+
+
 // ============================================ Grid =============================================
 
 mod grid {
     use std::fmt::{Debug, Display, Formatter};
     use std::error::Error;
-    use super::Coord;
+    use std::ops::{Index, IndexMut};
+    use std::collections::HashMap;
+    use super::{Coord, GridMove, moves_from};
 
 
+    #[derive(Clone, Eq, PartialEq, Hash)]
     pub struct Grid<T> {
         bound: Coord,
         data: Vec<T>,
@@ -385,19 +750,58 @@ mod grid {
 
 
     impl<T> Grid<T> {
-        // FIXME: As a library, maybe get() and set() should returns errors instead of
-        //   panicking when the coord is out of bounds. But this is more convenient.
+        /// Returns the index into data for coord, or None if it is out of bounds.
+        fn index_of(&self, coord: Coord) -> Option<usize> {
+            (coord.0 < self.bound.0 && coord.1 < self.bound.1).then(|| self.bound.0 * coord.1 + coord.0)
+        }
 
-        /// Gets the value at a location
+        /// Gets the value at a location. Panics if it is out of bounds; see try_get() for
+        /// a version that doesn't.
         pub fn get(&self, coord: Coord) -> &T {
-            assert!(coord < self.bound);
-            &self.data[self.bound.0 * coord.1 + coord.0]
+            self.try_get(coord).unwrap_or_else(|| panic!("Coord {} is outside the grid bound {}", coord, self.bound))
         }
 
-        /// Gets a mutable reference to the value at a location.
+        /// Gets a mutable reference to the value at a location. Panics if it is out of bounds.
         pub fn get_mut(&mut self, coord: Coord) -> &mut T {
-            assert!(coord < self.bound);
-            &mut self.data[self.bound.0 * coord.1 + coord.0]
+            let bound = self.bound;
+            self.try_get_mut(coord).unwrap_or_else(|| panic!("Coord {} is outside the grid bound {}", coord, bound))
+        }
+
+        /// Gets the value at a location, or None if it is out of bounds.
+        pub fn try_get(&self, coord: Coord) -> Option<&T> {
+            self.index_of(coord).map(|idx| &self.data[idx])
+        }
+
+        /// Gets a mutable reference to the value at a location, or None if it is out of
+        /// bounds.
+        pub fn try_get_mut(&mut self, coord: Coord) -> Option<&mut T> {
+            self.index_of(coord).map(|idx| &mut self.data[idx])
+        }
+
+        /// Gets the value at a location given as signed values, or None if it is out of
+        /// bounds (including being negative).
+        pub fn get_signed(&self, x: i64, y: i64) -> Option<&T> {
+            self.try_get(Coord(x.try_into().ok()?, y.try_into().ok()?))
+        }
+
+        /// Gets the value at a location, treating the grid as repeating forever in every
+        /// direction (so it is a torus). Panics if the grid is empty.
+        pub fn get_wrapped(&self, x: i64, y: i64) -> &T {
+            self.get(self.wrap(x, y))
+        }
+
+        /// Gets a mutable reference to the value at a location, treating the grid as
+        /// repeating forever in every direction. Panics if the grid is empty.
+        pub fn get_wrapped_mut(&mut self, x: i64, y: i64) -> &mut T {
+            let coord = self.wrap(x, y);
+            self.get_mut(coord)
+        }
+
+        /// Returns the Coord within the grid that (x, y) maps to if the grid repeats
+        /// forever in every direction.
+        pub fn wrap(&self, x: i64, y: i64) -> Coord {
+            assert!(self.bound.area() > 0, "Cannot wrap in an empty grid");
+            Coord(x.rem_euclid(self.bound.0 as i64) as usize, y.rem_euclid(self.bound.1 as i64) as usize)
         }
 
         /// Sets a value at a location.
@@ -409,6 +813,25 @@ mod grid {
         pub fn bound(&self) -> Coord {
             self.bound
         }
+
+        /// Returns the moves that stay within the Grid when starting from the given location.
+        pub fn moves_from(&self, start: Coord) -> Vec<GridMove> {
+            moves_from(start, self.bound)
+        }
+    }
+
+    impl<T> Index<Coord> for Grid<T> {
+        type Output = T;
+
+        fn index(&self, coord: Coord) -> &Self::Output {
+            self.get(coord)
+        }
+    }
+
+    impl<T> IndexMut<Coord> for Grid<T> {
+        fn index_mut(&mut self, coord: Coord) -> &mut Self::Output {
+            self.get_mut(coord)
+        }
     }
 
     impl<T: Debug> Debug for Grid<T> {
@@ -449,6 +872,16 @@ mod grid {
     pub enum GridReadError<ItemE: Error> {
         ItemConversionError(ItemE),
         RowsOfUnevenLength,
+        /// One of the other errors, along with where in the input it was found. The line
+        /// and column start from 1.
+        At{line: usize, column: usize, error: Box<GridReadError<ItemE>>},
+    }
+
+    impl<ItemE: Error> GridReadError<ItemE> {
+        /// Wraps this error to say where it was found.
+        fn at(self, line: usize, column: usize) -> Self {
+            GridReadError::At{line, column, error: Box::new(self)}
+        }
     }
 
     impl<ItemE: Error> Display for GridReadError<ItemE> {
@@ -460,6 +893,9 @@ mod grid {
                 GridReadError::RowsOfUnevenLength => {
                     write!(f, "rows are of uneven length")
                 }
+                GridReadError::At{line, column, error} => {
+                    write!(f, "{} at line {}, column {}", error, line, column)
+                }
             }
         }
     }
@@ -486,34 +922,86 @@ mod grid {
     }
 
 
-    impl<T: TryFrom<char, Error=ItemE>, ItemE: Error> Grid<T> {
-        /// If you happen to have a grid of characters (in an &str) in which each character
-        /// indicates a particular item, and there is a TryFrom to create a T from a character,
-        /// then this function can be used to construct a Grid.
-        pub fn from_char_string(s: &str, ) -> Result<Self, GridReadError<ItemE>> {
+    impl<T> Grid<T> {
+        /// Reads a rectangular grid of characters, calling convert() on each character
+        /// (along with its Coord) to get the item.
+        fn read_chars<ItemE, F>(s: &str, mut convert: F) -> Result<Self, GridReadError<ItemE>>
+            where ItemE: Error, F: FnMut(char, Coord) -> Result<T, ItemE>
+        {
             let mut width = 0;
             let mut height = 0;
             let mut data: Vec<T> = Vec::new();
             for (y, line) in s.lines().enumerate() {
                 let mut row_width = 0;
                 for (x, c) in line.chars().enumerate() {
-                    data.push(c.try_into()?);
-                    assert_eq!(row_width, x);
+                    let item = convert(c, Coord(x, y)).map_err(|err| {
+                        GridReadError::from(err).at(y + 1, x + 1)
+                    })?;
+                    data.push(item);
                     row_width += 1;
                 }
                 if y == 0 {
                     width = row_width;
-                } else {
-                    if row_width != width {
-                        return Err(GridReadError::RowsOfUnevenLength);
-                    }
+                } else if row_width != width {
+                    let column = std::cmp::min(row_width, width) + 1;
+                    return Err(GridReadError::RowsOfUnevenLength.at(y + 1, column));
                 }
-                assert_eq!(height, y);
                 height += 1;
             }
             let bound = Coord(width, height);
             Ok(Self { bound, data })
         }
+
+        /// Like from_char_string(), but convert() turns each character into an item. This is
+        /// for item types that can't have a TryFrom<char> of their own (like bool; see
+        /// bool_from_char()).
+        pub fn from_char_string_with<ItemE, F>(s: &str, convert: F) -> Result<Self, GridReadError<ItemE>>
+            where ItemE: Error, F: Fn(char) -> Result<T, ItemE>
+        {
+            Self::read_chars(s, |c, _| convert(c))
+        }
+    }
+
+    /// The locations of each marker character found by from_char_string_with_markers().
+    pub type MarkerCoords = HashMap<char, Vec<Coord>>;
+
+    impl<T: TryFrom<char, Error=ItemE>, ItemE: Error> Grid<T> {
+        /// If you happen to have a grid of characters (in an &str) in which each character
+        /// indicates a particular item, and there is a TryFrom to create a T from a character,
+        /// then this function can be used to construct a Grid.
+        pub fn from_char_string(s: &str, ) -> Result<Self, GridReadError<ItemE>> {
+            Self::read_chars(s, |c, _| c.try_into())
+        }
+
+        /// Like from_char_string(), but some characters are markers for special locations
+        /// (like a start and an end). Whenever marker(c) returns Some(item), the cell gets
+        /// that item and its Coord is recorded under c. Returns the grid along with the
+        /// Coords of each marker, in the order they were found (by rows).
+        pub fn from_char_string_with_marker_fn<F>(s: &str, marker: F) -> Result<(Self, MarkerCoords), GridReadError<ItemE>>
+            where F: Fn(char) -> Option<T>
+        {
+            let mut found: MarkerCoords = HashMap::new();
+            let grid = Self::read_chars(s, |c, coord| {
+                match marker(c) {
+                    Some(item) => {
+                        found.entry(c).or_default().push(coord);
+                        Ok(item)
+                    }
+                    None => c.try_into(),
+                }
+            })?;
+            Ok((grid, found))
+        }
+    }
+
+    impl<T: TryFrom<char, Error=ItemE> + Clone, ItemE: Error> Grid<T> {
+        /// Like from_char_string_with_marker_fn(), but the markers are given as a list of
+        /// (marker character, item to put in that cell).
+        pub fn from_char_string_with_markers(s: &str, markers: &[(char, T)]) -> Result<(Self, MarkerCoords), GridReadError<ItemE>> {
+            Self::from_char_string_with_marker_fn(s, |c| {
+                markers.iter().find(|(m, _)| *m == c).map(|(_, item)| item.clone())
+            })
+        }
     }
 
     impl<T> IntoIterator for Grid<T> {
@@ -553,6 +1041,18 @@ mod grid {
         pub fn iter_mut(&mut self) -> impl Iterator<Item=&mut T> {
             (&mut self.data).iter_mut()
         }
+
+        /// Iterate over the coords in the array along with (references to) the items. Will
+        /// go rows-first.
+        pub fn iter_with_coords(&self) -> impl Iterator<Item=(Coord, &T)> {
+            self.bound.range_by_rows().zip(self.data.iter())
+        }
+
+        /// Iterate over the coords in the array along with (mutable references to) the
+        /// items. Will go rows-first.
+        pub fn iter_with_coords_mut(&mut self) -> impl Iterator<Item=(Coord, &mut T)> {
+            self.bound.range_by_rows().zip(self.data.iter_mut())
+        }
     }
 
 
@@ -615,4 +1115,2187 @@ mod grid {
             Self { bound, data }
         }
     }
+
+    /// This converts from an iterator of (Coord,T) into a Grid<T>. The bound will be just
+    /// large enough to hold all the coords given. It will panic if there isn't exactly one
+    /// value for each Coord.
+    impl<T> FromIterator<(Coord, T)> for Grid<T> {
+        fn from_iter<U: IntoIterator<Item=(Coord, T)>>(iter: U) -> Self {
+            let staging: Vec<(Coord, T)> = iter.into_iter().collect();
+            let width = staging.iter().map(|(c,_)| c.0 + 1).max().unwrap_or(0);
+            let height = staging.iter().map(|(c,_)| c.1 + 1).max().unwrap_or(0);
+            let bound = Coord(width, height);
+
+            let mut slots: Vec<Option<T>> = bound.range_by_rows().map(|_| None).collect();
+            for (coord, val) in staging {
+                let slot = &mut slots[width * coord.1 + coord.0];
+                assert!(slot.is_none(), "Two values provided for coordinate {:?}", coord);
+                *slot = Some(val);
+            }
+            let data: Vec<T> = slots.into_iter()
+                .enumerate()
+                .map(|(idx, slot)| slot.unwrap_or_else(|| panic!("No value provided for coordinate {:?}", Coord(idx % width, idx / width))))
+                .collect();
+            Self { bound, data }
+        }
+    }
+
+
+
+    // ========================== TESTS ==========================
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        #[test]
+        fn test_checked_access() {
+            let mut grid: Grid<usize> = Grid::from_function(Coord(3, 2), |c| c.0 + 10 * c.1);
+            assert_eq!(grid.try_get(Coord(2, 1)), Some(&12));
+            assert_eq!(grid.try_get(Coord(3, 0)), None);
+            assert_eq!(grid.get_signed(1, 1), Some(&11));
+            assert_eq!(grid.get_signed(-1, 0), None);
+            assert_eq!(*grid.get_wrapped(-1, 3), 12);
+            assert_eq!(*grid.get_wrapped(7, -4), 1);
+            grid[Coord(0, 1)] = 99;
+            assert_eq!(grid[Coord(0, 1)], 99);
+            let found: Vec<Coord> = grid.iter_with_coords().filter(|(_, v)| **v > 10).map(|(c, _)| c).collect();
+            assert_eq!(found, vec![Coord(0, 1), Coord(1, 1), Coord(2, 1)]);
+        }
+
+        /// A cell that is open or a wall, for testing from_char_string().
+        #[derive(Debug, Clone, Eq, PartialEq)]
+        enum Cell {
+            Open,
+            Wall,
+        }
+
+        #[derive(Debug)]
+        struct BadCell(char);
+
+        impl Display for BadCell {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                write!(f, "invalid cell '{}'", self.0)
+            }
+        }
+
+        impl Error for BadCell {}
+
+        impl TryFrom<char> for Cell {
+            type Error = BadCell;
+
+            fn try_from(value: char) -> Result<Self, Self::Error> {
+                match value {
+                    '.' => Ok(Cell::Open),
+                    '#' => Ok(Cell::Wall),
+                    _ => Err(BadCell(value)),
+                }
+            }
+        }
+
+        #[test]
+        fn test_read_with_markers() {
+            let (grid, markers) = Grid::<Cell>::from_char_string_with_markers(
+                "#S.#\n#..E\n",
+                &[('S', Cell::Open), ('E', Cell::Open)],
+            ).unwrap();
+            assert_eq!(grid.bound(), Coord(4, 2));
+            assert_eq!(markers[&'S'], vec![Coord(1, 0)]);
+            assert_eq!(markers[&'E'], vec![Coord(3, 1)]);
+            assert_eq!(grid[Coord(3, 1)], Cell::Open);
+
+            let (_, digits) = Grid::<Cell>::from_char_string_with_marker_fn(
+                "#0.1#\n#2..#\n",
+                |c| c.is_ascii_digit().then_some(Cell::Open),
+            ).unwrap();
+            assert_eq!(digits.len(), 3);
+            assert_eq!(digits[&'2'], vec![Coord(1, 1)]);
+        }
+
+        #[test]
+        fn test_read_errors() {
+            let err = Grid::<Cell>::from_char_string("#..#\n#.x#\n").unwrap_err();
+            assert_eq!(err.to_string(), "error converting item invalid cell 'x' at line 2, column 3");
+            assert!(matches!(
+                err,
+                GridReadError::At{line: 2, column: 3, error} if matches!(*error, GridReadError::ItemConversionError(_))
+            ));
+            let err = Grid::<Cell>::from_char_string("#..#\n#.#\n").unwrap_err();
+            assert!(matches!(
+                err,
+                GridReadError::At{line: 2, column: 4, error} if matches!(*error, GridReadError::RowsOfUnevenLength)
+            ));
+        }
+
+        #[test]
+        #[should_panic(expected = "outside the grid")]
+        fn test_get_out_of_bounds() {
+            let grid: Grid<usize> = Grid::new_default(Coord(3, 2));
+            grid.get(Coord(1, 2));
+        }
+    }
+}
+
+
+// ============================================ Transforms =============================================
+
+mod transform {
+    use std::fmt::{Display, Formatter};
+    use super::{Coord, Grid};
+
+
+    impl<T: Clone> Grid<T> {
+        /// Returns a copy of this grid rotated 90 degrees clockwise.
+        pub fn rotate90(&self) -> Grid<T> {
+            let Coord(width, height) = self.bound();
+            Grid::from_function(Coord(height, width), |c| self.get(Coord(c.1, height - 1 - c.0)).clone())
+        }
+
+        /// Returns a copy of this grid rotated 180 degrees.
+        pub fn rotate180(&self) -> Grid<T> {
+            let Coord(width, height) = self.bound();
+            Grid::from_function(self.bound(), |c| self.get(Coord(width - 1 - c.0, height - 1 - c.1)).clone())
+        }
+
+        /// Returns a copy of this grid rotated 270 degrees clockwise (which is 90 degrees
+        /// counter-clockwise).
+        pub fn rotate270(&self) -> Grid<T> {
+            let Coord(width, height) = self.bound();
+            Grid::from_function(Coord(height, width), |c| self.get(Coord(width - 1 - c.1, c.0)).clone())
+        }
+
+        /// Returns a copy of this grid mirrored left-to-right.
+        pub fn flip_horizontal(&self) -> Grid<T> {
+            let width = self.bound().0;
+            Grid::from_function(self.bound(), |c| self.get(Coord(width - 1 - c.0, c.1)).clone())
+        }
+
+        /// Returns a copy of this grid mirrored top-to-bottom.
+        pub fn flip_vertical(&self) -> Grid<T> {
+            let height = self.bound().1;
+            Grid::from_function(self.bound(), |c| self.get(Coord(c.0, height - 1 - c.1)).clone())
+        }
+
+        /// Returns a copy of this grid with x and y swapped.
+        pub fn transpose(&self) -> Grid<T> {
+            let Coord(width, height) = self.bound();
+            Grid::from_function(Coord(height, width), |c| self.get(Coord(c.1, c.0)).clone())
+        }
+    }
+
+
+    /// A borrowed rectangular piece of a Grid. Coords used with it are relative to its
+    /// origin, so Coord(0,0) is the top-left corner of the view.
+    #[derive(Debug)]
+    pub struct SubGrid<'a, T> {
+        grid: &'a Grid<T>,
+        origin: Coord,
+        size: Coord,
+    }
+
+    // Derive would require T: Clone and T: Copy, which isn't needed for a reference.
+    impl<'a, T> Clone for SubGrid<'a, T> {
+        fn clone(&self) -> Self {
+            *self
+        }
+    }
+
+    impl<'a, T> Copy for SubGrid<'a, T> {}
+
+    impl<T> Grid<T> {
+        /// Returns a view of the part of this grid starting at origin and extending for
+        /// size. Panics if that doesn't fit within the grid.
+        pub fn subgrid(&self, origin: Coord, size: Coord) -> SubGrid<'_, T> {
+            let bound = self.bound();
+            assert!(origin.0 + size.0 <= bound.0 && origin.1 + size.1 <= bound.1, "subgrid does not fit in the grid");
+            SubGrid{grid: self, origin, size}
+        }
+
+        /// Returns a view of the entire grid.
+        pub fn view(&self) -> SubGrid<'_, T> {
+            self.subgrid(Coord(0, 0), self.bound())
+        }
+
+        /// Returns an iterator of the rows of the grid, each of which is an iterator going
+        /// from west to east.
+        pub fn rows(&self) -> impl Iterator<Item=impl Iterator<Item=&T>> {
+            self.view().rows()
+        }
+
+        /// Returns an iterator of the columns of the grid, each of which is an iterator
+        /// going from north to south.
+        pub fn columns(&self) -> impl Iterator<Item=impl Iterator<Item=&T>> {
+            self.view().columns()
+        }
+    }
+
+    impl<'a, T> SubGrid<'a, T> {
+        /// Returns the size of this view.
+        pub fn bound(&self) -> Coord {
+            self.size
+        }
+
+        /// Returns where (in the underlying grid) this view starts.
+        pub fn origin(&self) -> Coord {
+            self.origin
+        }
+
+        /// Gets the value at a location (relative to the origin of the view).
+        pub fn get(&self, coord: Coord) -> &'a T {
+            assert!(coord < self.size);
+            self.grid.get(self.origin + coord)
+        }
+
+        /// Returns an iterator of the values in row y, going from west to east.
+        pub fn row(&self, y: usize) -> impl Iterator<Item=&'a T> {
+            let view = *self;
+            (0..self.size.0).map(move |x| view.get(Coord(x, y)))
+        }
+
+        /// Returns an iterator of the values in column x, going from north to south.
+        pub fn column(&self, x: usize) -> impl Iterator<Item=&'a T> {
+            let view = *self;
+            (0..self.size.1).map(move |y| view.get(Coord(x, y)))
+        }
+
+        /// Returns an iterator of the rows of the view.
+        pub fn rows(&self) -> impl Iterator<Item=impl Iterator<Item=&'a T>> {
+            let view = *self;
+            (0..self.size.1).map(move |y| view.row(y))
+        }
+
+        /// Returns an iterator of the columns of the view.
+        pub fn columns(&self) -> impl Iterator<Item=impl Iterator<Item=&'a T>> {
+            let view = *self;
+            (0..self.size.0).map(move |x| view.column(x))
+        }
+
+        /// Returns a view of part of this view (coords are relative to this view).
+        pub fn subgrid(&self, origin: Coord, size: Coord) -> SubGrid<'a, T> {
+            assert!(origin.0 + size.0 <= self.size.0 && origin.1 + size.1 <= self.size.1, "subgrid does not fit in the view");
+            SubGrid{grid: self.grid, origin: self.origin + origin, size}
+        }
+    }
+
+    impl<'a, T: Clone> SubGrid<'a, T> {
+        /// Copies the contents of this view into a new Grid.
+        pub fn to_grid(&self) -> Grid<T> {
+            Grid::from_function(self.size, |c| self.get(c).clone())
+        }
+    }
+
+    impl<'a, T: Display> Display for SubGrid<'a, T> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            for row in self.rows() {
+                for val in row {
+                    write!(f, "{}", val)?;
+                }
+                writeln!(f)?;
+            }
+            Ok(())
+        }
+    }
+
+
+    // ========================== TESTS ==========================
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        /// A 3 wide by 2 tall grid.
+        fn sample() -> Grid<char> {
+            let rows = ["abc", "def"];
+            Grid::from_function(Coord(3, 2), |c| rows[c.1].as_bytes()[c.0] as char)
+        }
+
+        fn to_strings(grid: &Grid<char>) -> Vec<String> {
+            grid.rows().map(|row| row.collect()).collect()
+        }
+
+        #[test]
+        fn test_rotate_and_flip() {
+            let grid = sample();
+            assert_eq!(to_strings(&grid.rotate90()), vec!["da", "eb", "fc"]);
+            assert_eq!(to_strings(&grid.rotate180()), vec!["fed", "cba"]);
+            assert_eq!(to_strings(&grid.rotate270()), vec!["cf", "be", "ad"]);
+            assert_eq!(to_strings(&grid.flip_horizontal()), vec!["cba", "fed"]);
+            assert_eq!(to_strings(&grid.flip_vertical()), vec!["def", "abc"]);
+            assert_eq!(to_strings(&grid.transpose()), vec!["ad", "be", "cf"]);
+            assert_eq!(grid.rotate90().rotate270(), grid);
+            assert_eq!(grid.rotate90().rotate90(), grid.rotate180());
+        }
+
+        #[test]
+        fn test_subgrid() {
+            let grid = sample();
+            let view = grid.subgrid(Coord(1, 0), Coord(2, 2));
+            assert_eq!(*view.get(Coord(0, 1)), 'e');
+            assert_eq!(view.to_string(), "bc\nef\n");
+            let columns: Vec<String> = view.columns().map(|col| col.collect()).collect();
+            assert_eq!(columns, vec!["be", "cf"]);
+            assert_eq!(to_strings(&view.subgrid(Coord(1, 1), Coord(1, 1)).to_grid()), vec!["f"]);
+        }
+    }
+}
+
+
+// ============================================ ToChar =============================================
+
+mod to_char {
+    use std::error::Error;
+    use std::fmt::{Display, Formatter};
+    use super::{Coord, Grid, SubGrid};
+
+    /// The reverse of the TryFrom<char> used by Grid::from_char_string(): gives the
+    /// character that a cell is written as.
+    pub trait ToChar {
+        fn to_char(&self) -> char;
+    }
+
+    impl ToChar for char {
+        fn to_char(&self) -> char {
+            *self
+        }
+    }
+
+    /// Booleans are written the way most puzzles draw them: '#' for true and '.' for false.
+    impl ToChar for bool {
+        fn to_char(&self) -> char {
+            if *self {'#'} else {'.'}
+        }
+    }
+
+    /// The error returned by bool_from_char() for a character other than '#' or '.'.
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    pub struct InvalidChar(pub char);
+
+    impl Display for InvalidChar {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "invalid character '{}'", self.0)
+        }
+    }
+
+    impl Error for InvalidChar {}
+
+    /// The reverse of ToChar for bool: '#' is true and '.' is false. bool can't have a
+    /// TryFrom<char>, so pass this to Grid::from_char_string_with() instead.
+    pub fn bool_from_char(c: char) -> Result<bool, InvalidChar> {
+        match c {
+            '#' => Ok(true),
+            '.' => Ok(false),
+            _ => Err(InvalidChar(c)),
+        }
+    }
+
+    impl<T: ToChar> Grid<T> {
+        /// Writes the grid as lines of characters (each ending with a newline), in the
+        /// same format that from_char_string() reads.
+        pub fn to_char_string(&self) -> String {
+            self.view().to_char_string()
+        }
+    }
+
+    impl<'a, T: ToChar> SubGrid<'a, T> {
+        /// Writes the view as lines of characters (each ending with a newline).
+        pub fn to_char_string(&self) -> String {
+            let mut answer = String::with_capacity((self.bound().0 + 1) * self.bound().1);
+            for row in self.rows() {
+                answer.extend(row.map(|val| val.to_char()));
+                answer.push('\n');
+            }
+            answer
+        }
+    }
+
+    impl<T> Grid<T> {
+        /// Returns a view of the smallest rectangle that contains every cell where keep()
+        /// is true, or None if there are no such cells.
+        pub fn crop_to<P>(&self, keep: P) -> Option<SubGrid<'_, T>>
+            where P: Fn(&T) -> bool
+        {
+            let mut kept = self.iter_with_coords().filter(|(_, val)| keep(val)).map(|(c, _)| c);
+            let first = kept.next()?;
+            let (min, max) = kept.fold((first, first), |(min, max), c| {
+                (Coord(min.0.min(c.0), min.1.min(c.1)), Coord(max.0.max(c.0), max.1.max(c.1)))
+            });
+            Some(self.subgrid(min, Coord(max.0 - min.0 + 1, max.1 - min.1 + 1)))
+        }
+    }
+
+
+    // ========================== TESTS ==========================
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        #[test]
+        fn test_round_trip() {
+            let text = "#..\n.#.\n";
+            let grid: Grid<char> = Grid::from_char_string(text).unwrap();
+            assert_eq!(grid.to_char_string(), text);
+            let bools = Grid::from_function(grid.bound(), |c| *grid.get(c) == '#');
+            assert_eq!(bools.to_char_string(), text);
+            let read_bools: Grid<bool> = Grid::from_char_string_with(text, bool_from_char).unwrap();
+            assert!(read_bools == bools);
+            assert_eq!(read_bools.to_char_string(), text);
+            let err = Grid::from_char_string_with("#.\n.x\n", bool_from_char).unwrap_err();
+            assert_eq!(err.to_string(), "error converting item invalid character 'x' at line 2, column 2");
+        }
+
+        #[test]
+        fn test_crop_to() {
+            let grid: Grid<char> = Grid::from_char_string(".....\n..#..\n...#.\n.....\n").unwrap();
+            let cropped = grid.crop_to(|c| *c == '#').unwrap();
+            assert_eq!(cropped.origin(), Coord(2, 1));
+            assert_eq!(cropped.to_char_string(), "#.\n.#\n");
+            assert!(grid.crop_to(|c| *c == 'x').is_none());
+        }
+    }
+}
+
+
+// ============================================ Rays =============================================
+
+mod rays {
+    use std::iter;
+    use super::{Coord, Direction, Direction8, Grid, ICoord};
+
+
+    /// Returns the points on the straight line from a to b (including both ends), using
+    /// Bresenham's algorithm. Horizontal, vertical and 45 degree lines are exact; others
+    /// pick the nearest cell at each step.
+    pub fn line_between(a: ICoord, b: ICoord) -> Vec<ICoord> {
+        let dx = (b.0 - a.0).abs();
+        let dy = -(b.1 - a.1).abs();
+        let sx = (b.0 - a.0).signum();
+        let sy = (b.1 - a.1).signum();
+        let mut err = dx + dy;
+        let mut pos = a;
+        let mut answer = vec![pos];
+        while pos != b {
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                pos.0 += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                pos.1 += sy;
+            }
+            answer.push(pos);
+        }
+        answer
+    }
+
+    impl<T> Grid<T> {
+        /// Returns an iterator of the cells going in direction dir from start (but NOT
+        /// including start itself) until reaching the edge of the grid.
+        pub fn ray(&self, start: Coord, dir: Direction) -> impl Iterator<Item=(Coord, &T)> {
+            let bound = self.bound();
+            iter::successors(Some(start), move |c| c.bounded_step(dir, bound))
+                .skip(1)
+                .map(|c| (c, self.get(c)))
+        }
+
+        /// Like ray(), but the direction can be diagonal.
+        pub fn ray8(&self, start: Coord, dir: Direction8) -> impl Iterator<Item=(Coord, &T)> {
+            let bound = self.bound();
+            iter::successors(Some(start), move |c| c.bounded_step8(dir, bound))
+                .skip(1)
+                .map(|c| (c, self.get(c)))
+        }
+
+        /// Returns the cells along a ray (see ray()) up to AND including the first one where
+        /// stop() is true. This is the set of cells that can be seen from start if stop()
+        /// tells what blocks the view.
+        pub fn ray_until<P>(&self, start: Coord, dir: Direction, stop: P) -> impl Iterator<Item=(Coord, &T)>
+            where P: Fn(&T) -> bool
+        {
+            let mut done = false;
+            self.ray(start, dir).take_while(move |(_, val)| {
+                let keep = !done;
+                done = done || stop(val);
+                keep
+            })
+        }
+
+        /// Returns the first cell along a ray (see ray()) where hit() is true, or None if
+        /// the ray reaches the edge first.
+        pub fn first_hit<P>(&self, start: Coord, dir: Direction, hit: P) -> Option<(Coord, &T)>
+            where P: Fn(&T) -> bool
+        {
+            self.ray(start, dir).find(|(_, val)| hit(val))
+        }
+
+        /// Returns where something moving from start in direction dir would stop if it
+        /// keeps going until the next cell is blocked or is off the edge of the grid. If it
+        /// can't move at all this returns start.
+        pub fn slide<P>(&self, start: Coord, dir: Direction, blocked: P) -> Coord
+            where P: Fn(&T) -> bool
+        {
+            self.ray(start, dir)
+                .take_while(|(_, val)| !blocked(val))
+                .last()
+                .map_or(start, |(c, _)| c)
+        }
+
+        /// Returns the cells on the straight line from a to b (including both ends); see
+        /// line_between(). Panics if a or b is outside the grid.
+        pub fn line(&self, a: Coord, b: Coord) -> impl Iterator<Item=(Coord, &T)> {
+            line_between(a.into(), b.into()).into_iter()
+                .map(|c| {
+                    let c: Coord = c.try_into().unwrap();
+                    (c, self.get(c))
+                })
+        }
+    }
+
+
+    // ========================== TESTS ==========================
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        /// The tree heights from Advent of Code 2022 day 8.
+        fn trees() -> Grid<u8> {
+            let rows = ["30373", "25512", "65332", "33549", "35390"];
+            Grid::from_function(Coord(5, 5), |c| rows[c.1].as_bytes()[c.0] - b'0')
+        }
+
+        #[test]
+        fn test_ray() {
+            let grid = trees();
+            let east: Vec<u8> = grid.ray(Coord(1, 2), Direction::East).map(|(_, h)| *h).collect();
+            assert_eq!(east, vec![3, 3, 2]);
+            assert_eq!(grid.ray(Coord(0, 0), Direction::North).count(), 0);
+            let diagonal: Vec<Coord> = grid.ray8(Coord(2, 2), Direction8::SouthEast).map(|(c, _)| c).collect();
+            assert_eq!(diagonal, vec![Coord(3, 3), Coord(4, 4)]);
+        }
+
+        #[test]
+        fn test_viewing_distance() {
+            let grid = trees();
+            let house = Coord(2, 3);
+            let height = *grid.get(house);
+            let score: usize = Direction::ALL.iter()
+                .map(|dir| grid.ray_until(house, *dir, |h| *h >= height).count())
+                .product();
+            assert_eq!(score, 8);
+            assert_eq!(grid.first_hit(house, Direction::North, |h| *h >= height), Some((Coord(2, 1), &5)));
+            assert_eq!(grid.first_hit(house, Direction::South, |h| *h >= height), None);
+        }
+
+        #[test]
+        fn test_slide() {
+            let rows = ["O.#.", "...."];
+            let grid = Grid::from_function(Coord(4, 2), |c| rows[c.1].as_bytes()[c.0] as char);
+            assert_eq!(grid.slide(Coord(0, 0), Direction::East, |c| *c == '#'), Coord(1, 0));
+            assert_eq!(grid.slide(Coord(0, 0), Direction::North, |c| *c == '#'), Coord(0, 0));
+            assert_eq!(grid.slide(Coord(0, 1), Direction::East, |c| *c == '#'), Coord(3, 1));
+        }
+
+        #[test]
+        fn test_line_between() {
+            assert_eq!(line_between(ICoord(0, 0), ICoord(3, 0)).len(), 4);
+            assert_eq!(line_between(ICoord(2, 2), ICoord(-1, -1)),
+                       vec![ICoord(2, 2), ICoord(1, 1), ICoord(0, 0), ICoord(-1, -1)]);
+            assert_eq!(line_between(ICoord(0, 0), ICoord(4, 2)),
+                       vec![ICoord(0, 0), ICoord(1, 1), ICoord(2, 1), ICoord(3, 2), ICoord(4, 2)]);
+            let grid = trees();
+            let heights: Vec<u8> = grid.line(Coord(0, 4), Coord(4, 0)).map(|(_, h)| *h).collect();
+            assert_eq!(heights, vec![3, 3, 3, 1, 3]);
+        }
+    }
+}
+
+
+// ============================================ BitGrid =============================================
+
+mod bit_grid {
+    use std::fmt::{Debug, Display, Formatter};
+    use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
+    use super::{Coord, Direction, Grid};
+
+    const WORD_BITS: usize = u64::BITS as usize;
+
+    /// A grid of booleans packed 64 to a word. Each row starts a new word, and any bits
+    /// past the end of a row are always kept zero. This makes counting and whole-grid
+    /// operations (and, or, shifting) much faster than a Grid<bool>.
+    #[derive(Clone, Eq, PartialEq, Hash)]
+    pub struct BitGrid {
+        bound: Coord,
+        words_per_row: usize,
+        words: Vec<u64>,
+    }
+
+    impl BitGrid {
+        /// Creates a BitGrid of the given size with every cell false.
+        pub fn new(bound: Coord) -> Self {
+            let words_per_row = bound.0.div_ceil(WORD_BITS);
+            BitGrid{bound, words_per_row, words: vec![0; words_per_row * bound.1]}
+        }
+
+        /// Returns the bound on the BitGrid.
+        pub fn bound(&self) -> Coord {
+            self.bound
+        }
+
+        /// Returns (index of word, bit within word) for a coord. Panics if it is out of bounds.
+        fn locate(&self, coord: Coord) -> (usize, u64) {
+            assert!(coord < self.bound, "Coord {} is outside the grid bound {}", coord, self.bound);
+            (coord.1 * self.words_per_row + coord.0 / WORD_BITS, 1 << (coord.0 % WORD_BITS))
+        }
+
+        /// Gets the value at a location.
+        pub fn get(&self, coord: Coord) -> bool {
+            let (idx, bit) = self.locate(coord);
+            self.words[idx] & bit != 0
+        }
+
+        /// Sets the value at a location.
+        pub fn set(&mut self, coord: Coord, val: bool) {
+            let (idx, bit) = self.locate(coord);
+            if val {
+                self.words[idx] |= bit;
+            } else {
+                self.words[idx] &= !bit;
+            }
+        }
+
+        /// Flips the value at a location.
+        pub fn toggle(&mut self, coord: Coord) {
+            let (idx, bit) = self.locate(coord);
+            self.words[idx] ^= bit;
+        }
+
+        /// Returns the number of cells that are true.
+        pub fn count_ones(&self) -> usize {
+            self.words.iter().map(|w| w.count_ones() as usize).sum()
+        }
+
+        /// Returns the number of cells in row y that are true.
+        pub fn count_ones_in_row(&self, y: usize) -> usize {
+            self.row_words(y).iter().map(|w| w.count_ones() as usize).sum()
+        }
+
+        /// Iterate over the coords of the cells that are true. Will go rows-first.
+        pub fn iter_ones(&self) -> impl Iterator<Item=Coord> + '_ {
+            self.words.iter().enumerate().flat_map(move |(idx, word)| {
+                let y = idx / self.words_per_row;
+                let x_base = (idx % self.words_per_row) * WORD_BITS;
+                let mut w = *word;
+                std::iter::from_fn(move || {
+                    (w != 0).then(|| {
+                        let bit = w.trailing_zeros() as usize;
+                        w &= w - 1;
+                        Coord(x_base + bit, y)
+                    })
+                })
+            })
+        }
+
+        /// The words that hold row y.
+        fn row_words(&self, y: usize) -> &[u64] {
+            &self.words[y * self.words_per_row .. (y + 1) * self.words_per_row]
+        }
+
+        /// The mask of which bits are in use in the last word of each row.
+        fn last_word_mask(&self) -> u64 {
+            match self.bound.0 % WORD_BITS {
+                0 => u64::MAX,
+                n => (1 << n) - 1,
+            }
+        }
+
+        /// Clears any bits past the end of each row.
+        fn clear_padding(&mut self) {
+            if self.words_per_row > 0 {
+                let mask = self.last_word_mask();
+                for row in self.words.chunks_mut(self.words_per_row) {
+                    *row.last_mut().unwrap() &= mask;
+                }
+            }
+        }
+
+        /// Sets every cell in the rectangle starting at origin with the given size.
+        pub fn set_rect(&mut self, origin: Coord, size: Coord, val: bool) {
+            for c in size.range_by_rows() {
+                self.set(origin + c, val);
+            }
+        }
+
+        /// Flips every cell in the rectangle starting at origin with the given size.
+        pub fn toggle_rect(&mut self, origin: Coord, size: Coord) {
+            for c in size.range_by_rows() {
+                self.toggle(origin + c);
+            }
+        }
+
+        /// Rotates row y to the east by n cells; cells that fall off the east edge come
+        /// back on the west edge.
+        pub fn rotate_row(&mut self, y: usize, n: usize) {
+            assert!(y < self.bound.1, "Row {} is outside the grid bound {}", y, self.bound);
+            let width = self.bound.0;
+            if width == 0 {
+                return;
+            }
+            let n = n % width;
+            if n == 0 {
+                return;
+            }
+            let wpr = self.words_per_row;
+            let mask = self.last_word_mask();
+            let row = &mut self.words[y * wpr .. (y + 1) * wpr];
+            if wpr == 1 {
+                row[0] = ((row[0] << n) | (row[0] >> (width - n))) & mask;
+            } else {
+                let src = row.to_vec();
+                for (i, word) in row.iter_mut().enumerate() {
+                    *word = shifted_word_up(&src, n, i) | shifted_word_down(&src, width - n, i);
+                }
+                row[wpr - 1] &= mask;
+            }
+        }
+
+        /// Rotates column x to the south by n cells; cells that fall off the south edge
+        /// come back on the north edge.
+        pub fn rotate_column(&mut self, x: usize, n: usize) {
+            assert!(x < self.bound.0, "Column {} is outside the grid bound {}", x, self.bound);
+            let height = self.bound.1;
+            if height == 0 {
+                return;
+            }
+            let n = n % height;
+            if n == 0 {
+                return;
+            }
+            // rotating by n is the same as reversing it all, then each of the two parts
+            self.reverse_column(x, 0, height);
+            self.reverse_column(x, 0, n);
+            self.reverse_column(x, n, height);
+        }
+
+        /// Reverses the cells of column x from row start up to (but not including) row end.
+        fn reverse_column(&mut self, x: usize, start: usize, end: usize) {
+            let wpr = self.words_per_row;
+            let (offset, bit) = (x / WORD_BITS, 1 << (x % WORD_BITS));
+            let (mut lo, mut hi) = (start, end);
+            while lo + 1 < hi {
+                hi -= 1;
+                let (a, b) = (lo * wpr + offset, hi * wpr + offset);
+                if (self.words[a] ^ self.words[b]) & bit != 0 {
+                    self.words[a] ^= bit;
+                    self.words[b] ^= bit;
+                }
+                lo += 1;
+            }
+        }
+
+        /// Returns a copy of this grid with every cell moved one step in direction dir.
+        /// Cells that move off the edge are lost and the cells left empty are false.
+        pub fn shifted(&self, dir: Direction) -> BitGrid {
+            let wpr = self.words_per_row;
+            let mut answer = BitGrid::new(self.bound);
+            if wpr == 0 || self.bound.1 == 0 {
+                return answer;
+            }
+            match dir {
+                Direction::North => {
+                    answer.words[.. self.words.len() - wpr].copy_from_slice(&self.words[wpr ..]);
+                }
+                Direction::South => {
+                    answer.words[wpr ..].copy_from_slice(&self.words[.. self.words.len() - wpr]);
+                }
+                Direction::East => {
+                    for (src, dst) in self.words.chunks(wpr).zip(answer.words.chunks_mut(wpr)) {
+                        for i in 0..wpr {
+                            let carry = if i > 0 {src[i - 1] >> (WORD_BITS - 1)} else {0};
+                            dst[i] = (src[i] << 1) | carry;
+                        }
+                    }
+                    answer.clear_padding();
+                }
+                Direction::West => {
+                    for (src, dst) in self.words.chunks(wpr).zip(answer.words.chunks_mut(wpr)) {
+                        for i in 0..wpr {
+                            let carry = if i + 1 < wpr {src[i + 1] << (WORD_BITS - 1)} else {0};
+                            dst[i] = (src[i] >> 1) | carry;
+                        }
+                    }
+                }
+            }
+            answer
+        }
+
+        /// Applies op to each pair of words. Panics if the grids are different sizes.
+        fn combine<F: Fn(u64, u64) -> u64>(&mut self, other: &BitGrid, op: F) {
+            assert_eq!(self.bound, other.bound, "BitGrids must be the same size");
+            for (a, b) in self.words.iter_mut().zip(other.words.iter()) {
+                *a = op(*a, *b);
+            }
+        }
+    }
+
+    /// Treating words as one long run of bits (bit 0 of word 0 first), returns word i of
+    /// what you get by moving every bit k places higher.
+    fn shifted_word_up(words: &[u64], k: usize, i: usize) -> u64 {
+        let (skip, bits) = (k / WORD_BITS, k % WORD_BITS);
+        if i < skip {
+            return 0;
+        }
+        let carry = match bits {
+            0 => 0,
+            _ if i > skip => words[i - skip - 1] >> (WORD_BITS - bits),
+            _ => 0,
+        };
+        (words[i - skip] << bits) | carry
+    }
+
+    /// Treating words as one long run of bits (bit 0 of word 0 first), returns word i of
+    /// what you get by moving every bit k places lower.
+    fn shifted_word_down(words: &[u64], k: usize, i: usize) -> u64 {
+        let (skip, bits) = (k / WORD_BITS, k % WORD_BITS);
+        if i + skip >= words.len() {
+            return 0;
+        }
+        let carry = match bits {
+            0 => 0,
+            _ if i + skip + 1 < words.len() => words[i + skip + 1] << (WORD_BITS - bits),
+            _ => 0,
+        };
+        (words[i + skip] >> bits) | carry
+    }
+
+    impl From<&Grid<bool>> for BitGrid {
+        fn from(grid: &Grid<bool>) -> Self {
+            let mut answer = BitGrid::new(grid.bound());
+            for (coord, val) in grid.iter_with_coords() {
+                if *val {
+                    answer.set(coord, true);
+                }
+            }
+            answer
+        }
+    }
+
+    impl From<&BitGrid> for Grid<bool> {
+        fn from(bits: &BitGrid) -> Self {
+            Grid::from_function(bits.bound(), |c| bits.get(c))
+        }
+    }
+
+    impl BitAndAssign<&BitGrid> for BitGrid {
+        fn bitand_assign(&mut self, rhs: &BitGrid) {
+            self.combine(rhs, |a, b| a & b);
+        }
+    }
+
+    impl BitOrAssign<&BitGrid> for BitGrid {
+        fn bitor_assign(&mut self, rhs: &BitGrid) {
+            self.combine(rhs, |a, b| a | b);
+        }
+    }
+
+    impl BitXorAssign<&BitGrid> for BitGrid {
+        fn bitxor_assign(&mut self, rhs: &BitGrid) {
+            self.combine(rhs, |a, b| a ^ b);
+        }
+    }
+
+    impl BitAnd for &BitGrid {
+        type Output = BitGrid;
+
+        fn bitand(self, rhs: Self) -> Self::Output {
+            let mut answer = self.clone();
+            answer &= rhs;
+            answer
+        }
+    }
+
+    impl BitOr for &BitGrid {
+        type Output = BitGrid;
+
+        fn bitor(self, rhs: Self) -> Self::Output {
+            let mut answer = self.clone();
+            answer |= rhs;
+            answer
+        }
+    }
+
+    impl BitXor for &BitGrid {
+        type Output = BitGrid;
+
+        fn bitxor(self, rhs: Self) -> Self::Output {
+            let mut answer = self.clone();
+            answer ^= rhs;
+            answer
+        }
+    }
+
+    impl Not for &BitGrid {
+        type Output = BitGrid;
+
+        fn not(self) -> Self::Output {
+            let mut answer = self.clone();
+            for w in answer.words.iter_mut() {
+                *w = !*w;
+            }
+            answer.clear_padding();
+            answer
+        }
+    }
+
+    /// Displays as '#' for true and '.' for false.
+    impl Display for BitGrid {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            for y in 0..self.bound.1 {
+                for x in 0..self.bound.0 {
+                    write!(f, "{}", if self.get(Coord(x, y)) {'#'} else {'.'})?;
+                }
+                writeln!(f)?;
+            }
+            Ok(())
+        }
+    }
+
+    impl Debug for BitGrid {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            writeln!(f)?;
+            Display::fmt(self, f)
+        }
+    }
+
+
+    // ========================== TESTS ==========================
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        #[test]
+        fn test_screen() {
+            // The example from Advent of Code 2016 day 8.
+            let mut screen = BitGrid::new(Coord(7, 3));
+            screen.set_rect(Coord(0, 0), Coord(3, 2), true);
+            screen.rotate_column(1, 1);
+            screen.rotate_row(0, 4);
+            screen.rotate_column(1, 1);
+            assert_eq!(screen.to_string(), ".#..#.#\n#.#....\n.#.....\n");
+            assert_eq!(screen.count_ones(), 6);
+            assert_eq!(screen.count_ones_in_row(0), 3);
+        }
+
+        #[test]
+        fn test_shift_across_words() {
+            let mut grid = BitGrid::new(Coord(130, 2));
+            grid.set(Coord(63, 0), true);
+            grid.set(Coord(129, 1), true);
+            let east = grid.shifted(Direction::East);
+            assert_eq!(east.iter_ones().collect::<Vec<_>>(), vec![Coord(64, 0)]);
+            let west = grid.shifted(Direction::West);
+            assert_eq!(west.iter_ones().collect::<Vec<_>>(), vec![Coord(62, 0), Coord(128, 1)]);
+            let north = grid.shifted(Direction::North);
+            assert_eq!(north.iter_ones().collect::<Vec<_>>(), vec![Coord(129, 0)]);
+            assert_eq!(grid.shifted(Direction::South).count_ones(), 1);
+            assert_eq!(BitGrid::new(Coord(5, 0)).shifted(Direction::North).count_ones(), 0);
+        }
+
+        #[test]
+        fn test_rotate_across_words() {
+            let mut grid = BitGrid::new(Coord(130, 130));
+            for x in [0, 63, 64, 100, 129] {
+                grid.set(Coord(x, 5), true);
+                grid.set(Coord(5, x), true);
+            }
+            for n in [0, 1, 63, 64, 65, 129, 130, 200] {
+                let mut by_row = grid.clone();
+                by_row.rotate_row(5, n);
+                let mut by_column = grid.clone();
+                by_column.rotate_column(5, n);
+                for i in 0..130 {
+                    assert_eq!(by_row.get(Coord((i + n) % 130, 5)), grid.get(Coord(i, 5)));
+                    assert_eq!(by_column.get(Coord(5, (i + n) % 130)), grid.get(Coord(5, i)));
+                }
+                assert_eq!(by_row.count_ones(), grid.count_ones());
+                assert_eq!(by_column.count_ones(), grid.count_ones());
+            }
+        }
+
+        #[test]
+        fn test_bitwise_ops() {
+            let mut a = BitGrid::new(Coord(70, 1));
+            let mut b = BitGrid::new(Coord(70, 1));
+            a.set_rect(Coord(0, 0), Coord(40, 1), true);
+            b.set_rect(Coord(30, 0), Coord(40, 1), true);
+            assert_eq!((&a & &b).count_ones(), 10);
+            assert_eq!((&a | &b).count_ones(), 70);
+            assert_eq!((&a ^ &b).count_ones(), 60);
+            assert_eq!((!&a).count_ones(), 30);
+            let grid: Grid<bool> = (&a).into();
+            assert_eq!(BitGrid::from(&grid), a);
+        }
+    }
+}
+
+
+// ============================================ Diff =============================================
+
+mod diff {
+    use std::collections::HashSet;
+    use std::fmt::Display;
+    use std::ops::Deref;
+    use super::{Coord, Grid};
+
+    /// ANSI codes to turn inverse video on and off, used to highlight cells.
+    const HIGHLIGHT_ON: &str = "\x1b[7m";
+    const HIGHLIGHT_OFF: &str = "\x1b[0m";
+
+
+    impl<T: PartialEq> Grid<T> {
+        /// Returns the coords (by rows) where this grid differs from other. Panics if the
+        /// grids are not the same size.
+        pub fn diff(&self, other: &Grid<T>) -> Vec<Coord> {
+            assert_eq!(self.bound(), other.bound(), "Can only diff grids of the same size");
+            self.iter_with_coords()
+                .zip(other.iter())
+                .filter(|((_, a), b)| a != b)
+                .map(|((c, _), _)| c)
+                .collect()
+        }
+    }
+
+    impl<T: Display> Grid<T> {
+        /// Renders the grid as text (like Display does), except that the cells in
+        /// highlight are shown in inverse video.
+        pub fn to_highlighted_string(&self, highlight: &HashSet<Coord>) -> String {
+            self.rendered_rows(highlight).into_iter()
+                .map(|row| row + "\n")
+                .collect()
+        }
+
+        /// Returns each row of the grid as a string, with highlighting.
+        fn rendered_rows(&self, highlight: &HashSet<Coord>) -> Vec<String> {
+            (0..self.bound().1)
+                .map(|y| {
+                    (0..self.bound().0)
+                        .map(|x| {
+                            let c = Coord(x, y);
+                            if highlight.contains(&c) {
+                                format!("{}{}{}", HIGHLIGHT_ON, self.get(c), HIGHLIGHT_OFF)
+                            } else {
+                                self.get(c).to_string()
+                            }
+                        })
+                        .collect()
+                })
+                .collect()
+        }
+    }
+
+    impl<T: Display + PartialEq> Grid<T> {
+        /// Renders this grid and other next to each other (this one on the left) with the
+        /// cells that differ highlighted in both. Panics if the grids are not the same size.
+        pub fn side_by_side(&self, other: &Grid<T>) -> String {
+            let changed: HashSet<Coord> = self.diff(other).into_iter().collect();
+            self.rendered_rows(&changed).into_iter()
+                .zip(other.rendered_rows(&changed))
+                .map(|(left, right)| format!("{}   {}\n", left, right))
+                .collect()
+        }
+    }
+
+
+    /// One change made to a TrackedGrid.
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    pub struct Change<T> {
+        pub coord: Coord,
+        pub old: T,
+        pub new: T,
+    }
+
+    /// A Grid that keeps a log of every change made through set(). It derefs to the Grid,
+    /// so all the read-only methods can be used directly.
+    #[derive(Debug, Clone)]
+    pub struct TrackedGrid<T> {
+        grid: Grid<T>,
+        changes: Vec<Change<T>>,
+        enabled: bool,
+    }
+
+    impl<T: Clone + PartialEq> TrackedGrid<T> {
+        /// Wraps a grid, starting with an empty (and enabled) change log.
+        pub fn new(grid: Grid<T>) -> Self {
+            TrackedGrid{grid, changes: Vec::new(), enabled: true}
+        }
+
+        /// Turns recording of changes on or off.
+        pub fn set_tracking(&mut self, enabled: bool) {
+            self.enabled = enabled;
+        }
+
+        /// Sets a value at a location, recording the change if the value is different and
+        /// tracking is on.
+        pub fn set(&mut self, coord: Coord, val: T) {
+            let old = self.grid.get(coord);
+            if self.enabled && *old != val {
+                self.changes.push(Change{coord, old: old.clone(), new: val.clone()});
+            }
+            self.grid.set(coord, val);
+        }
+
+        /// Returns the changes recorded so far, oldest first.
+        pub fn changes(&self) -> &[Change<T>] {
+            &self.changes
+        }
+
+        /// Returns the changes recorded so far and clears the log.
+        pub fn take_changes(&mut self) -> Vec<Change<T>> {
+            std::mem::take(&mut self.changes)
+        }
+
+        /// Unwraps the grid, discarding the change log.
+        pub fn into_inner(self) -> Grid<T> {
+            self.grid
+        }
+    }
+
+    impl<T> Deref for TrackedGrid<T> {
+        type Target = Grid<T>;
+
+        fn deref(&self) -> &Self::Target {
+            &self.grid
+        }
+    }
+
+
+    // ========================== TESTS ==========================
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        #[test]
+        fn test_diff_and_render() {
+            let before = Grid::from_function(Coord(3, 2), |c| if c == Coord(1, 0) {'O'} else {'.'});
+            let after = Grid::from_function(Coord(3, 2), |c| if c == Coord(1, 1) {'O'} else {'.'});
+            assert_eq!(before.diff(&after), vec![Coord(1, 0), Coord(1, 1)]);
+            assert_eq!(before.diff(&before), vec![]);
+            let highlighted = after.to_highlighted_string(&HashSet::from([Coord(1, 1)]));
+            assert_eq!(highlighted, "...\n.\x1b[7mO\x1b[0m.\n");
+            let side = before.side_by_side(&after);
+            assert_eq!(side.lines().count(), 2);
+            assert!(side.starts_with(".\x1b[7mO\x1b[0m.   .\x1b[7m.\x1b[0m.\n"));
+        }
+
+        #[test]
+        fn test_tracked_grid() {
+            let mut grid = TrackedGrid::new(Grid::from_function(Coord(2, 2), |_| 0));
+            grid.set(Coord(0, 1), 5);
+            grid.set(Coord(1, 1), 0); // not a change
+            grid.set_tracking(false);
+            grid.set(Coord(1, 0), 7);
+            grid.set_tracking(true);
+            grid.set(Coord(0, 1), 6);
+            assert_eq!(*grid.get(Coord(1, 0)), 7);
+            let changes = grid.take_changes();
+            assert_eq!(changes, vec![
+                Change{coord: Coord(0, 1), old: 0, new: 5},
+                Change{coord: Coord(0, 1), old: 5, new: 6},
+            ]);
+            assert!(grid.changes().is_empty());
+        }
+    }
+}
+
+
+// ============================================ SparseGrid =============================================
+
+mod sparse_grid {
+    use std::collections::HashMap;
+    use std::fmt::{Display, Formatter};
+    use super::ICoord;
+
+    /// A grid with no edges: any ICoord can be used, and cells that have never been set
+    /// hold a default value. It keeps track of the bounding box of the cells that have been
+    /// set, which is also the region that gets displayed.
+    #[derive(Debug, Clone)]
+    pub struct SparseGrid<T> {
+        default: T,
+        data: HashMap<ICoord, T>,
+        bounds: Option<(ICoord, ICoord)>, // (min, max), inclusive; None if empty
+    }
+
+    impl<T> SparseGrid<T> {
+        /// Creates an empty SparseGrid where every cell has the default value.
+        pub fn new(default: T) -> Self {
+            SparseGrid{default, data: HashMap::new(), bounds: None}
+        }
+
+        /// Gets the value at a location.
+        pub fn get(&self, coord: ICoord) -> &T {
+            self.data.get(&coord).unwrap_or(&self.default)
+        }
+
+        /// Returns true if the location has been set (even if it was set to the default).
+        pub fn contains(&self, coord: ICoord) -> bool {
+            self.data.contains_key(&coord)
+        }
+
+        /// Sets a value at a location.
+        pub fn set(&mut self, coord: ICoord, val: T) {
+            self.grow_bounds(coord);
+            self.data.insert(coord, val);
+        }
+
+        /// Removes the value at a location (so it goes back to the default), returning the
+        /// value that was there if it had been set.
+        pub fn remove(&mut self, coord: ICoord) -> Option<T> {
+            let answer = self.data.remove(&coord);
+            if let Some((min, max)) = self.bounds {
+                if answer.is_some() && (coord.0 == min.0 || coord.0 == max.0 || coord.1 == min.1 || coord.1 == max.1) {
+                    // it was on the edge, so the bounds might shrink
+                    self.bounds = None;
+                    let coords: Vec<ICoord> = self.data.keys().copied().collect();
+                    for c in coords {
+                        self.grow_bounds(c);
+                    }
+                }
+            }
+            answer
+        }
+
+        /// Returns the number of locations that have been set.
+        pub fn len(&self) -> usize {
+            self.data.len()
+        }
+
+        /// Returns true if no locations have been set.
+        pub fn is_empty(&self) -> bool {
+            self.data.is_empty()
+        }
+
+        /// Returns (min, max) -- the corners (inclusive) of the smallest rectangle that holds
+        /// every location that has been set, or None if nothing has been set.
+        pub fn bounds(&self) -> Option<(ICoord, ICoord)> {
+            self.bounds
+        }
+
+        /// Iterate over the locations that have been set and their values, in no
+        /// particular order.
+        pub fn iter(&self) -> impl Iterator<Item=(ICoord, &T)> {
+            self.data.iter().map(|(c, v)| (*c, v))
+        }
+
+        /// Expands bounds to include coord.
+        fn grow_bounds(&mut self, coord: ICoord) {
+            self.bounds = Some(match self.bounds {
+                None => (coord, coord),
+                Some((min, max)) => (
+                    ICoord(min.0.min(coord.0), min.1.min(coord.1)),
+                    ICoord(max.0.max(coord.0), max.1.max(coord.1)),
+                ),
+            });
+        }
+    }
+
+    impl<T: Clone> SparseGrid<T> {
+        /// Gets a mutable reference to the value at a location, setting it to the default
+        /// first if it had not been set.
+        pub fn get_mut(&mut self, coord: ICoord) -> &mut T {
+            self.grow_bounds(coord);
+            let default = &self.default;
+            self.data.entry(coord).or_insert_with(|| default.clone())
+        }
+    }
+
+    /// Displays the rectangle given by bounds().
+    impl<T: Display> Display for SparseGrid<T> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            if let Some((min, max)) = self.bounds {
+                for y in min.1 ..= max.1 {
+                    for x in min.0 ..= max.0 {
+                        write!(f, "{}", self.get(ICoord(x, y)))?;
+                    }
+                    writeln!(f)?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+
+    // ========================== TESTS ==========================
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        #[test]
+        fn test_bounds_and_display() {
+            let mut grid = SparseGrid::new('.');
+            assert_eq!(grid.bounds(), None);
+            grid.set(ICoord(-2, 1), '#');
+            grid.set(ICoord(1, -1), '#');
+            *grid.get_mut(ICoord(0, 0)) = '@';
+            assert_eq!(grid.bounds(), Some((ICoord(-2, -1), ICoord(1, 1))));
+            assert_eq!(*grid.get(ICoord(100, -100)), '.');
+            assert_eq!(grid.to_string(), "...#\n..@.\n#...\n");
+
+            assert_eq!(grid.remove(ICoord(-2, 1)), Some('#'));
+            assert_eq!(grid.bounds(), Some((ICoord(0, -1), ICoord(1, 0))));
+            assert_eq!(grid.len(), 2);
+        }
+    }
+}
+
+
+// ============================================ Pathfinding =============================================
+
+mod pathfinding {
+    use std::collections::HashMap;
+    use std::fmt::{Display, Formatter};
+    use std::hash::{Hash, Hasher};
+    use crate::astar::{costs_within, solve_with_weighted_astar, State};
+    use super::{Coord, Grid};
+
+    /// Which cells count as adjacent when moving around a Grid.
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    pub enum Neighborhood {
+        Four, // only north, south, east and west
+        Eight, // also the diagonals
+    }
+
+    impl Neighborhood {
+        /// Returns the neighbors of coord within bound.
+        pub fn neighbors(&self, coord: Coord, bound: Coord) -> Vec<Coord> {
+            match self {
+                Neighborhood::Four => coord.neighbors(bound),
+                Neighborhood::Eight => coord.neighbors8(bound),
+            }
+        }
+
+        /// Returns the fewest steps it could take to go from c1 to c2.
+        fn min_steps(&self, c1: Coord, c2: Coord) -> usize {
+            match self {
+                Neighborhood::Four => c1.taxicab_dist(c2),
+                Neighborhood::Eight => std::cmp::max(c1.0.abs_diff(c2.0), c1.1.abs_diff(c2.1)),
+            }
+        }
+    }
+
+
+    /// Something made of cells that can be walked across one step at a time, such as a Grid
+    /// or a TiledGrid. The path finding in this module works on anything that implements it.
+    pub trait Walkable {
+        type Pos: Copy + Eq + Hash + Display;
+        type Cell;
+
+        /// Returns each position one step (as defined by neighborhood) away from pos which
+        /// passable(from, to) allows moving to, along with cost(to) -- the cost of entering it.
+        fn steps_from(
+            &self,
+            pos: Self::Pos,
+            neighborhood: Neighborhood,
+            passable: &dyn Fn(&Self::Cell, &Self::Cell) -> bool,
+            cost: &dyn Fn(&Self::Cell) -> usize,
+        ) -> Vec<(Self::Pos, usize)>;
+
+        /// Returns the fewest steps it could take to go from one position to another.
+        fn min_steps(&self, neighborhood: Neighborhood, from: Self::Pos, to: Self::Pos) -> usize;
+    }
+
+    impl<T> Walkable for Grid<T> {
+        type Pos = Coord;
+        type Cell = T;
+
+        fn steps_from(
+            &self,
+            pos: Coord,
+            neighborhood: Neighborhood,
+            passable: &dyn Fn(&T, &T) -> bool,
+            cost: &dyn Fn(&T) -> usize,
+        ) -> Vec<(Coord, usize)> {
+            let here = self.get(pos);
+            neighborhood.neighbors(pos, self.bound()).into_iter()
+                .filter(|c| passable(here, self.get(*c)))
+                .map(|c| (c, cost(self.get(c))))
+                .collect()
+        }
+
+        fn min_steps(&self, neighborhood: Neighborhood, from: Coord, to: Coord) -> usize {
+            neighborhood.min_steps(from, to)
+        }
+    }
+
+
+    /// The State used when searching for a path across something Walkable. Only pos matters
+    /// for equality; everything else is the same for every state in one search.
+    struct PathState<'a, W: Walkable> {
+        walk: &'a W,
+        pos: W::Pos,
+        goal: Option<W::Pos>, // None when we are finding distances to everywhere
+        neighborhood: Neighborhood,
+        min_cost: usize, // the cheapest cost of entering any cell, for the heuristic
+        passable: &'a dyn Fn(&W::Cell, &W::Cell) -> bool,
+        cost: &'a dyn Fn(&W::Cell) -> usize,
+    }
+
+    impl<'a, W: Walkable> Clone for PathState<'a, W> {
+        fn clone(&self) -> Self {
+            PathState{pos: self.pos, ..*self}
+        }
+    }
+
+    impl<'a, W: Walkable> PartialEq for PathState<'a, W> {
+        fn eq(&self, other: &Self) -> bool {
+            self.pos == other.pos
+        }
+    }
+
+    impl<'a, W: Walkable> Eq for PathState<'a, W> {}
+
+    impl<'a, W: Walkable> Hash for PathState<'a, W> {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.pos.hash(state);
+        }
+    }
+
+    impl<'a, W: Walkable> Display for PathState<'a, W> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "@{}", self.pos)
+        }
+    }
+
+    impl<'a, W: Walkable> State for PathState<'a, W> {
+        type TMove = (W::Pos, usize); // where we step to, and what it costs
+
+        fn is_winning(&self) -> bool {
+            self.goal == Some(self.pos)
+        }
+
+        fn min_moves_to_win(&self) -> usize {
+            match self.goal {
+                Some(goal) => self.walk.min_steps(self.neighborhood, self.pos, goal) * self.min_cost,
+                None => 0,
+            }
+        }
+
+        fn avail_moves(&self) -> Vec<Self::TMove> {
+            self.walk.steps_from(self.pos, self.neighborhood, self.passable, self.cost)
+        }
+
+        fn enact_move(&self, mv: &Self::TMove) -> Self {
+            PathState{pos: mv.0, ..*self}
+        }
+
+        fn move_cost(&self, mv: &Self::TMove) -> usize {
+            mv.1
+        }
+    }
+
+
+    /// Finds the cost of the cheapest path from start to every position that can be reached
+    /// for at most max_cost. This is what Grid::distances_from() and
+    /// TiledGrid::distances_from() are built on.
+    pub(super) fn costs_from<W: Walkable>(
+        walk: &W,
+        start: W::Pos,
+        neighborhood: Neighborhood,
+        max_cost: usize,
+        passable: &dyn Fn(&W::Cell, &W::Cell) -> bool,
+        cost: &dyn Fn(&W::Cell) -> usize,
+    ) -> HashMap<W::Pos, usize> {
+        let initial_state = PathState{
+            walk,
+            pos: start,
+            goal: None,
+            neighborhood,
+            min_cost: 0,
+            passable,
+            cost,
+        };
+        costs_within(&initial_state, max_cost).into_iter()
+            .map(|(state, dist)| (state.pos, dist))
+            .collect()
+    }
+
+
+    impl<T> Grid<T> {
+        /// Finds the cheapest path from start to goal. Steps go to adjacent cells (as
+        /// defined by neighborhood), and a step is only allowed if passable(from, to) is true
+        /// for the values in the two cells. Each step costs cost(to) -- the cost of entering
+        /// the cell. Returns None if goal can't be reached, otherwise Some(total_cost, path)
+        /// where path lists every cell visited, including start and goal.
+        pub fn shortest_path<P, C>(
+            &self,
+            start: Coord,
+            goal: Coord,
+            neighborhood: Neighborhood,
+            passable: P,
+            cost: C,
+        ) -> Option<(usize, Vec<Coord>)>
+            where P: Fn(&T, &T) -> bool, C: Fn(&T) -> usize
+        {
+            assert!(start < self.bound() && goal < self.bound());
+            if start == goal {
+                return Some((0, vec![start]));
+            }
+            let min_cost = self.iter().map(&cost).min().unwrap_or(0);
+            let initial_state = PathState{
+                walk: self,
+                pos: start,
+                goal: Some(goal),
+                neighborhood,
+                min_cost,
+                passable: &passable,
+                cost: &cost,
+            };
+            solve_with_weighted_astar(&initial_state, 0).map(|moves| {
+                let total_cost = moves.iter().map(|(_, step_cost)| step_cost).sum();
+                let mut path = Vec::with_capacity(moves.len() + 1);
+                path.push(start);
+                path.extend(moves.into_iter().map(|(c, _)| c));
+                (total_cost, path)
+            })
+        }
+
+        /// Finds the cost of the cheapest path from start to every cell of the Grid, using
+        /// the same rules for passable and cost as shortest_path(). Returns a Grid of the
+        /// same size where unreachable cells are None.
+        pub fn distances_from<P, C>(
+            &self,
+            start: Coord,
+            neighborhood: Neighborhood,
+            passable: P,
+            cost: C,
+        ) -> Grid<Option<usize>>
+            where P: Fn(&T, &T) -> bool, C: Fn(&T) -> usize
+        {
+            assert!(start < self.bound());
+            let mut dists: Grid<Option<usize>> = Grid::from_function(self.bound(), |_| None);
+            for (coord, dist) in costs_from(self, start, neighborhood, usize::MAX, &passable, &cost) {
+                dists.set(coord, Some(dist));
+            }
+            dists
+        }
+    }
+
+
+    // ========================== TESTS ==========================
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        fn maze() -> Grid<char> {
+            Grid::from_function(Coord(5, 4), |c| {
+                ["..#..", ".##.#", "....#", "#.#.."][c.1].as_bytes()[c.0] as char
+            })
+        }
+
+        #[test]
+        fn test_shortest_path_four() {
+            let grid = maze();
+            let (cost, path) = grid.shortest_path(
+                Coord(0, 0), Coord(4, 3), Neighborhood::Four, |_, to| *to != '#', |_| 1
+            ).unwrap();
+            assert_eq!(cost, 7);
+            assert_eq!(path.len(), 8);
+            assert_eq!(path.first(), Some(&Coord(0, 0)));
+            assert_eq!(path.last(), Some(&Coord(4, 3)));
+            assert!(path.windows(2).all(|w| w[0].taxicab_dist(w[1]) == 1));
+            assert!(grid.shortest_path(Coord(0, 0), Coord(2, 0), Neighborhood::Four, |_, to| *to != '#', |_| 1).is_none());
+        }
+
+        #[test]
+        fn test_shortest_path_eight_and_cost() {
+            let grid = maze();
+            let (cost, _) = grid.shortest_path(
+                Coord(0, 0), Coord(4, 3), Neighborhood::Eight, |_, to| *to != '#', |_| 1
+            ).unwrap();
+            assert_eq!(cost, 5);
+            let weights: Grid<usize> = Grid::from_function(Coord(3, 3), |c| if c == Coord(1, 1) {9} else {1});
+            let (cost, path) = weights.shortest_path(
+                Coord(0, 1), Coord(2, 1), Neighborhood::Four, |_, _| true, |w| *w
+            ).unwrap();
+            assert_eq!(cost, 4);
+            assert!(!path.contains(&Coord(1, 1)));
+        }
+
+        #[test]
+        fn test_distances_from() {
+            let grid = maze();
+            let dists = grid.distances_from(Coord(0, 0), Neighborhood::Four, |_, to| *to != '#', |_| 1);
+            assert_eq!(*dists.get(Coord(0, 0)), Some(0));
+            assert_eq!(*dists.get(Coord(4, 3)), Some(7));
+            assert_eq!(*dists.get(Coord(4, 0)), Some(8));
+            assert_eq!(*dists.get(Coord(2, 0)), None);
+            assert_eq!(*dists.get(Coord(0, 3)), None);
+            let weights: Grid<usize> = Grid::from_function(Coord(3, 3), |c| if c == Coord(1, 1) {9} else {1});
+            let dists = weights.distances_from(Coord(0, 1), Neighborhood::Four, |_, _| true, |w| *w);
+            assert_eq!(*dists.get(Coord(2, 1)), Some(4));
+            assert_eq!(*dists.get(Coord(1, 1)), Some(9));
+        }
+    }
+}
+
+
+// ============================================ Regions =============================================
+
+mod regions {
+    use std::collections::VecDeque;
+    use super::{Coord, Grid, Neighborhood};
+
+
+    /// Identifies one connected component found by Grid::connected_components(). They are
+    /// numbered from 0 in the order that they are found scanning by rows.
+    pub type ComponentId = usize;
+
+    /// The result of Grid::connected_components().
+    #[derive(Debug, Clone)]
+    pub struct Components {
+        /// For each cell, the component it belongs to.
+        pub labels: Grid<ComponentId>,
+        /// For each component, the number of cells in it.
+        pub sizes: Vec<usize>,
+    }
+
+    impl Components {
+        /// Returns the number of components.
+        pub fn count(&self) -> usize {
+            self.sizes.len()
+        }
+
+        /// Returns the coords of every cell in the given component.
+        pub fn cells(&self, id: ComponentId) -> impl Iterator<Item=Coord> + '_ {
+            self.labels.bound().range_by_rows().filter(move |c| *self.labels.get(*c) == id)
+        }
+    }
+
+
+    /// Marks every cell reachable from any of the seeds by moving only onto cells where
+    /// include() is true. The seeds themselves are always marked.
+    fn flood<T, P>(grid: &Grid<T>, seeds: Vec<Coord>, neighborhood: Neighborhood, include: P) -> Grid<bool>
+        where P: Fn(&T) -> bool
+    {
+        let bound = grid.bound();
+        let mut reached: Grid<bool> = Grid::new_default(bound);
+        for seed in seeds.iter() {
+            reached.set(*seed, true);
+        }
+        let mut queue = VecDeque::from(seeds);
+        while let Some(coord) = queue.pop_front() {
+            for next in neighborhood.neighbors(coord, bound) {
+                if !*reached.get(next) && include(grid.get(next)) {
+                    reached.set(next, true);
+                    queue.push_back(next);
+                }
+            }
+        }
+        reached
+    }
+
+    impl<T> Grid<T> {
+        /// Returns a grid that is true for every cell that can be reached from start by
+        /// moving only onto cells where include() is true. The start is always included.
+        pub fn flood_fill<P>(&self, start: Coord, neighborhood: Neighborhood, include: P) -> Grid<bool>
+            where P: Fn(&T) -> bool
+        {
+            flood(self, vec![start], neighborhood, include)
+        }
+
+        /// Splits the grid into connected components. Two neighboring cells are in the
+        /// same component if connected(a, b) is true for their values; connected() should
+        /// be symmetric.
+        pub fn connected_components<P>(&self, neighborhood: Neighborhood, connected: P) -> Components
+            where P: Fn(&T, &T) -> bool
+        {
+            let bound = self.bound();
+            let mut labels: Grid<Option<ComponentId>> = Grid::new_default(bound);
+            let mut sizes: Vec<usize> = Vec::new();
+            for start in bound.range_by_rows() {
+                if labels.get(start).is_some() {
+                    continue;
+                }
+                let id = sizes.len();
+                let mut size = 1;
+                labels.set(start, Some(id));
+                let mut queue = VecDeque::from([start]);
+                while let Some(coord) = queue.pop_front() {
+                    for next in neighborhood.neighbors(coord, bound) {
+                        if labels.get(next).is_none() && connected(self.get(coord), self.get(next)) {
+                            labels.set(next, Some(id));
+                            size += 1;
+                            queue.push_back(next);
+                        }
+                    }
+                }
+                sizes.push(size);
+            }
+            let labels = Grid::from_function(bound, |c| labels.get(c).unwrap());
+            Components{labels, sizes}
+        }
+
+        /// Returns a grid that is true for every cell where open() is true but which can't
+        /// reach the edge of the grid by moving through other open cells. These are the
+        /// pockets that are completely enclosed by cells that aren't open.
+        pub fn enclosed_regions<P>(&self, neighborhood: Neighborhood, open: P) -> Grid<bool>
+            where P: Fn(&T) -> bool
+        {
+            let Coord(width, height) = self.bound();
+            let edge_seeds: Vec<Coord> = self.bound().range_by_rows()
+                .filter(|c| c.0 == 0 || c.1 == 0 || c.0 + 1 == width || c.1 + 1 == height)
+                .filter(|c| open(self.get(*c)))
+                .collect();
+            let outside = flood(self, edge_seeds, neighborhood, &open);
+            Grid::from_function(self.bound(), |c| open(self.get(c)) && !*outside.get(c))
+        }
+    }
+
+
+    // ========================== TESTS ==========================
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        fn parse(rows: &[&str]) -> Grid<char> {
+            let bound = Coord(rows[0].len(), rows.len());
+            Grid::from_function(bound, |c| rows[c.1].as_bytes()[c.0] as char)
+        }
+
+        fn count(grid: &Grid<bool>) -> usize {
+            grid.iter().filter(|b| **b).count()
+        }
+
+        #[test]
+        fn test_basins() {
+            // The example from Advent of Code 2021 day 9.
+            let heights = parse(&[
+                "2199943210",
+                "3987894921",
+                "9856789892",
+                "8767896789",
+                "9899965678",
+            ]);
+            let components = heights.connected_components(Neighborhood::Four, |a, b| (*a == '9') == (*b == '9'));
+            let mut basin_sizes: Vec<usize> = (0..components.count())
+                .filter(|id| *heights.get(components.cells(*id).next().unwrap()) != '9')
+                .map(|id| components.sizes[id])
+                .collect();
+            basin_sizes.sort();
+            assert_eq!(basin_sizes, vec![3, 9, 9, 14]);
+
+            let basin = heights.flood_fill(Coord(9, 0), Neighborhood::Four, |h| *h != '9');
+            assert_eq!(count(&basin), 9);
+        }
+
+        #[test]
+        fn test_enclosed_regions() {
+            let map = parse(&[
+                "#####.",
+                "#..#..",
+                "#..#.#",
+                "####..",
+            ]);
+            let enclosed = map.enclosed_regions(Neighborhood::Four, |c| *c == '.');
+            assert_eq!(count(&enclosed), 4);
+            assert!(*enclosed.get(Coord(2, 2)));
+            assert!(!*enclosed.get(Coord(4, 2)));
+            // with diagonal moves, the pocket at (1,1) can escape past the corner
+            let sealed = parse(&[
+                "###",
+                "#.#",
+                "##.",
+            ]);
+            assert_eq!(count(&sealed.enclosed_regions(Neighborhood::Four, |c| *c == '.')), 1);
+            assert_eq!(count(&sealed.enclosed_regions(Neighborhood::Eight, |c| *c == '.')), 0);
+        }
+    }
+}
+
+
+// ============================================ TiledGrid =============================================
+
+mod tiled {
+    use std::collections::HashMap;
+    use super::{Coord, Grid, ICoord, Neighborhood, Walkable};
+    use super::pathfinding::costs_from;
+
+
+    /// The function a TiledGrid uses to get the value of a cell in a given tile from the
+    /// value in the base grid.
+    type TileTransform<T> = Box<dyn Fn(ICoord, &T) -> T>;
+
+    /// A Grid repeated forever in every direction. Global coords are signed; the tile at
+    /// ICoord(0,0) is the base grid itself, ICoord(1,0) is the copy just east of it, and
+    /// so forth. Optionally the value in each tile can be computed from the tile and the
+    /// base value (eg: when the copies have increased risk levels).
+    pub struct TiledGrid<T> {
+        base: Grid<T>,
+        transform: Option<TileTransform<T>>,
+    }
+
+    impl<T: Clone> TiledGrid<T> {
+        /// Creates a TiledGrid where every tile is an exact copy of base. Panics if base is
+        /// empty.
+        pub fn new(base: Grid<T>) -> Self {
+            assert!(base.bound().area() > 0, "Cannot tile an empty grid");
+            TiledGrid{base, transform: None}
+        }
+
+        /// Creates a TiledGrid where the value of a cell is transform(tile, base_value).
+        /// Panics if base is empty.
+        pub fn with_transform<F>(base: Grid<T>, transform: F) -> Self
+            where F: Fn(ICoord, &T) -> T + 'static
+        {
+            assert!(base.bound().area() > 0, "Cannot tile an empty grid");
+            TiledGrid{base, transform: Some(Box::new(transform))}
+        }
+
+        /// Returns the grid that gets repeated.
+        pub fn base(&self) -> &Grid<T> {
+            &self.base
+        }
+
+        /// Returns the size of one tile.
+        pub fn tile_bound(&self) -> Coord {
+            self.base.bound()
+        }
+
+        /// Splits a global coord into (which tile, coord within that tile).
+        pub fn locate(&self, global: ICoord) -> (ICoord, Coord) {
+            let width = self.base.bound().0 as i64;
+            let height = self.base.bound().1 as i64;
+            let tile = ICoord(global.0.div_euclid(width), global.1.div_euclid(height));
+            let local = Coord(global.0.rem_euclid(width) as usize, global.1.rem_euclid(height) as usize);
+            (tile, local)
+        }
+
+        /// Combines a tile and a coord within it into a global coord.
+        pub fn to_global(&self, tile: ICoord, local: Coord) -> ICoord {
+            let bound = self.base.bound();
+            ICoord(tile.0 * bound.0 as i64 + local.0 as i64, tile.1 * bound.1 as i64 + local.1 as i64)
+        }
+
+        /// Gets the value at a global coord.
+        pub fn get(&self, global: ICoord) -> T {
+            let (tile, local) = self.locate(global);
+            let val = self.base.get(local);
+            match &self.transform {
+                Some(transform) => transform(tile, val),
+                None => val.clone(),
+            }
+        }
+
+        /// Builds an ordinary Grid from the tiles ICoord(0,0) up to (but not including)
+        /// tiles, so all of the Grid methods (like shortest_path()) can be used on it.
+        pub fn to_grid(&self, tiles: Coord) -> Grid<T> {
+            let bound = self.base.bound();
+            Grid::from_function(Coord(bound.0 * tiles.0, bound.1 * tiles.1), |c| self.get(c.into()))
+        }
+
+        /// Finds the cost of the cheapest path from start to every cell whose cost is at
+        /// most max_dist, using the same rules for passable and cost as
+        /// Grid::shortest_path(). Since the grid goes on forever, max_dist is what keeps
+        /// this from running forever.
+        pub fn distances_from<P, C>(
+            &self,
+            start: ICoord,
+            neighborhood: Neighborhood,
+            max_dist: usize,
+            passable: P,
+            cost: C,
+        ) -> HashMap<ICoord, usize>
+            where P: Fn(&T, &T) -> bool, C: Fn(&T) -> usize
+        {
+            costs_from(self, start, neighborhood, max_dist, &passable, &cost)
+        }
+    }
+
+    impl<T: Clone> Walkable for TiledGrid<T> {
+        type Pos = ICoord;
+        type Cell = T;
+
+        fn steps_from(
+            &self,
+            pos: ICoord,
+            neighborhood: Neighborhood,
+            passable: &dyn Fn(&T, &T) -> bool,
+            cost: &dyn Fn(&T) -> usize,
+        ) -> Vec<(ICoord, usize)> {
+            let here = self.get(pos);
+            let neighbors: Vec<ICoord> = match neighborhood {
+                Neighborhood::Four => pos.neighbors().to_vec(),
+                Neighborhood::Eight => pos.neighbors8().to_vec(),
+            };
+            neighbors.into_iter()
+                .filter_map(|next| {
+                    let there = self.get(next);
+                    passable(&here, &there).then(|| (next, cost(&there)))
+                })
+                .collect()
+        }
+
+        fn min_steps(&self, neighborhood: Neighborhood, from: ICoord, to: ICoord) -> usize {
+            match neighborhood {
+                Neighborhood::Four => from.taxicab_dist(to) as usize,
+                Neighborhood::Eight => std::cmp::max(from.0.abs_diff(to.0), from.1.abs_diff(to.1)) as usize,
+            }
+        }
+    }
+
+
+    // ========================== TESTS ==========================
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        fn parse(rows: &[&str]) -> Grid<char> {
+            Grid::from_function(Coord(rows[0].len(), rows.len()), |c| rows[c.1].as_bytes()[c.0] as char)
+        }
+
+        #[test]
+        fn test_locate() {
+            let tiled = TiledGrid::new(parse(&["abc", "def"]));
+            assert_eq!(tiled.locate(ICoord(-1, 0)), (ICoord(-1, 0), Coord(2, 0)));
+            assert_eq!(tiled.locate(ICoord(7, -3)), (ICoord(2, -2), Coord(1, 1)));
+            assert_eq!(tiled.to_global(ICoord(2, -2), Coord(1, 1)), ICoord(7, -3));
+            assert_eq!(tiled.get(ICoord(-4, 5)), 'f');
+        }
+
+        #[test]
+        fn test_expanded_cave() {
+            // The example from Advent of Code 2021 day 15.
+            let base = parse(&[
+                "1163751742", "1381373672", "2136511328", "3694931569", "7463417111",
+                "1319128137", "1359912421", "3125421639", "1293138521", "2311944581",
+            ]).iter().map(|c| c.to_digit(10).unwrap() as usize).collect::<Vec<usize>>();
+            let base = Grid::from_function(Coord(10, 10), |c| base[c.1 * 10 + c.0]);
+            let tiled = TiledGrid::with_transform(base, |tile, risk| {
+                (risk - 1 + (tile.0 + tile.1) as usize) % 9 + 1
+            });
+            assert_eq!(tiled.get(ICoord(49, 49)), 9);
+            let cave = tiled.to_grid(Coord(5, 5));
+            let (risk, _) = cave.shortest_path(Coord(0, 0), Coord(49, 49), Neighborhood::Four, |_, _| true, |r| *r).unwrap();
+            assert_eq!(risk, 315);
+        }
+
+        #[test]
+        fn test_infinite_garden() {
+            // The example from Advent of Code 2023 day 21.
+            let garden = parse(&[
+                "...........", ".....###.#.", ".###.##..#.", "..#.#...#..", "....#.#....",
+                ".##..S####.", ".##..#...#.", ".......##..", ".##.#.####.", ".##..##.##.",
+                "...........",
+            ]);
+            let tiled = TiledGrid::new(garden);
+            let reachable = |steps: usize| {
+                tiled.distances_from(ICoord(5, 5), Neighborhood::Four, steps, |_, to| *to != '#', |_| 1)
+                    .values()
+                    .filter(|d| *d % 2 == steps % 2)
+                    .count()
+            };
+            assert_eq!(reachable(6), 16);
+            assert_eq!(reachable(10), 50);
+            assert_eq!(reachable(50), 1594);
+        }
+    }
+}
+
+
+// ============================================ Timed =============================================
+
+mod timed {
+    use std::fmt::{Display, Formatter};
+    use std::hash::{Hash, Hasher};
+    use crate::astar::{solve_with_astar, State};
+    use super::{Coord, Grid};
+
+    /// Some puzzles have obstacles that move around the grid as time passes, but which
+    /// repeat after some period (eg: blizzards that wrap around the edges). This stores
+    /// which cells are blocked at each tick within one period, so that we can search over
+    /// (position, time) without re-deriving the obstacles each time.
+    pub struct PeriodicObstacles {
+        bound: Coord,
+        slices: Vec<Grid<bool>>, // slices[t] is true where a cell is blocked at time t
+    }
+
+    impl PeriodicObstacles {
+        /// Creates a PeriodicObstacles by calling blocked_at(t) for each t in 0..period to
+        /// get a Grid of which cells are blocked at that time. The grids must all be the
+        /// same size.
+        pub fn new<F>(period: usize, mut blocked_at: F) -> Self
+            where F: FnMut(usize) -> Grid<bool>
+        {
+            assert!(period > 0);
+            let slices: Vec<Grid<bool>> = (0..period).map(&mut blocked_at).collect();
+            let bound = slices[0].bound();
+            assert!(slices.iter().all(|slice| slice.bound() == bound));
+            PeriodicObstacles{bound, slices}
+        }
+
+        /// Returns the bound of the grid.
+        pub fn bound(&self) -> Coord {
+            self.bound
+        }
+
+        /// Returns the number of ticks before the obstacles repeat.
+        pub fn period(&self) -> usize {
+            self.slices.len()
+        }
+
+        /// Returns true if the coord is blocked at the given time (which can be any time,
+        /// not just one within the first period).
+        pub fn is_blocked(&self, coord: Coord, time: usize) -> bool {
+            *self.slices[time % self.period()].get(coord)
+        }
+
+        /// Finds the earliest time we can reach goal if we are at start at start_time. Each
+        /// tick we can either stay put or step to an adjacent cell, but we can never be in
+        /// a cell while it is blocked. Returns None if it can't be done, or else
+        /// Some(arrival_time, path) where path gives our position at each tick from
+        /// start_time to arrival_time.
+        pub fn earliest_arrival(&self, start: Coord, start_time: usize, goal: Coord) -> Option<(usize, Vec<Coord>)> {
+            assert!(start < self.bound && goal < self.bound);
+            if start == goal {
+                return Some((start_time, vec![start]));
+            }
+            let initial_state = TimedState{obstacles: self, pos: start, time: start_time, goal};
+            solve_with_astar(&initial_state, 0).map(|moves| {
+                let mut path = Vec::with_capacity(moves.len() + 1);
+                path.push(start);
+                path.extend(moves);
+                (start_time + path.len() - 1, path)
+            })
+        }
+    }
+
+
+    /// The State used by earliest_arrival(). A move is the position we will be in after
+    /// the next tick.
+    struct TimedState<'a> {
+        obstacles: &'a PeriodicObstacles,
+        pos: Coord,
+        time: usize,
+        goal: Coord,
+    }
+
+    impl<'a> Clone for TimedState<'a> {
+        fn clone(&self) -> Self {
+            TimedState{..*self}
+        }
+    }
+
+    impl<'a> PartialEq for TimedState<'a> {
+        fn eq(&self, other: &Self) -> bool {
+            self.pos == other.pos && self.time == other.time
+        }
+    }
+
+    impl<'a> Eq for TimedState<'a> {}
+
+    impl<'a> Hash for TimedState<'a> {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.pos.hash(state);
+            self.time.hash(state);
+        }
+    }
+
+    impl<'a> Display for TimedState<'a> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "@{} at time {}", self.pos, self.time)
+        }
+    }
+
+    impl<'a> State for TimedState<'a> {
+        type TMove = Coord;
+
+        fn is_winning(&self) -> bool {
+            self.pos == self.goal
+        }
+
+        fn min_moves_to_win(&self) -> usize {
+            self.pos.taxicab_dist(self.goal)
+        }
+
+        fn avail_moves(&self) -> Vec<Self::TMove> {
+            let next_time = self.time + 1;
+            let mut answer = self.pos.neighbors(self.obstacles.bound);
+            answer.push(self.pos); // waiting is allowed too
+            answer.retain(|c| !self.obstacles.is_blocked(*c, next_time));
+            answer
+        }
+
+        fn enact_move(&self, mv: &Self::TMove) -> Self {
+            TimedState{pos: *mv, time: self.time + 1, ..*self}
+        }
+
+        /// Being in the same place at the same point in the cycle is the same state, no
+        /// matter how many cycles have gone by.
+        fn canonical_key(&self) -> Self {
+            TimedState{time: self.time % self.obstacles.period(), ..*self}
+        }
+    }
+
+
+    // ========================== TESTS ==========================
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        /// The example from Advent of Code 2022 day 24: blizzards move one step per tick and
+        /// wrap around inside the walls.
+        fn blizzard_basin() -> PeriodicObstacles {
+            let map = [
+                "#.######",
+                "#>>.<^<#",
+                "#.<..<<#",
+                "#>v.><>#",
+                "#<^v^^>#",
+                "######.#",
+            ];
+            let bound = Coord(8, 6);
+            let (width, height) = (6, 4); // the inside of the walls
+            PeriodicObstacles::new(12, |t| {
+                let mut blocked = Grid::from_function(bound, |c| map[c.1].as_bytes()[c.0] == b'#');
+                for c in Coord(width, height).range_by_rows() {
+                    let (x, y) = (c.0 + width * t, c.1 + height * t); // big enough to never go negative
+                    let pos = match map[c.1 + 1].as_bytes()[c.0 + 1] {
+                        b'>' => Some(Coord((x + t) % width, c.1)),
+                        b'<' => Some(Coord((x - t) % width, c.1)),
+                        b'v' => Some(Coord(c.0, (y + t) % height)),
+                        b'^' => Some(Coord(c.0, (y - t) % height)),
+                        _ => None,
+                    };
+                    if let Some(pos) = pos {
+                        blocked.set(Coord(pos.0 + 1, pos.1 + 1), true);
+                    }
+                }
+                blocked
+            })
+        }
+
+        #[test]
+        fn test_earliest_arrival() {
+            let basin = blizzard_basin();
+            let start = Coord(1, 0);
+            let goal = Coord(6, 5);
+            let (arrival, path) = basin.earliest_arrival(start, 0, goal).unwrap();
+            assert_eq!(arrival, 18);
+            assert_eq!(path.len(), 19);
+            for (t, pos) in path.iter().enumerate() {
+                assert!(!basin.is_blocked(*pos, t));
+            }
+            let (back, _) = basin.earliest_arrival(goal, arrival, start).unwrap();
+            assert_eq!(back, 41);
+            let (again, _) = basin.earliest_arrival(start, back, goal).unwrap();
+            assert_eq!(again, 54);
+        }
+    }
 }
@@ -0,0 +1,602 @@
+//! Like grid, but in three dimensions. There's a dense Grid3 for when the space is small
+//! and full, and a PointSet3 for a scattering of points (like cubes or beacons).
+
+pub use coord3::Coord3;
+pub use icoord3::ICoord3;
+pub use neighborhood3::Neighborhood3;
+pub use bounding_box::BoundingBox3;
+pub use dense::Grid3;
+pub use point_set::PointSet3;
+
+
+// ============================================ Neighborhood3 =============================================
+
+mod neighborhood3 {
+
+    /// Which cells count as adjacent: the 6 that share a face or the 26 that share at least
+    /// a corner.
+    #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+    pub enum Neighborhood3 {
+        Six,
+        TwentySix,
+    }
+
+    /// The (dx, dy, dz) offsets of the 6 face neighbors.
+    pub(super) const OFFSETS_6: [(i64, i64, i64); 6] = [
+        (1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, 1), (0, 0, -1),
+    ];
+
+    /// The (dx, dy, dz) offsets of all 26 neighbors, in order of increasing z, then y, then x.
+    pub(super) const OFFSETS_26: [(i64, i64, i64); 26] = {
+        let mut answer = [(0, 0, 0); 26];
+        let mut i = 0;
+        let mut n = 0;
+        while n < 27 {
+            if n != 13 { // skip the center
+                answer[i] = (n % 3 - 1, (n / 3) % 3 - 1, n / 9 - 1);
+                i += 1;
+            }
+            n += 1;
+        }
+        answer
+    };
+
+    impl Neighborhood3 {
+        /// The (dx, dy, dz) offsets of the neighbors.
+        pub fn offsets(&self) -> &'static [(i64, i64, i64)] {
+            match self {
+                Neighborhood3::Six => &OFFSETS_6,
+                Neighborhood3::TwentySix => &OFFSETS_26,
+            }
+        }
+    }
+}
+
+
+// ============================================ Coord3 =============================================
+
+mod coord3 {
+    use std::fmt::{Display, Formatter};
+    use std::ops::Add;
+    use super::Neighborhood3;
+
+
+    #[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, Default)]
+    pub struct Coord3(pub usize, pub usize, pub usize);
+
+
+    impl Coord3 {
+        /// Convenient alias for coord.0.
+        pub fn x(&self) -> usize {
+            self.0
+        }
+
+        /// Convenient alias for coord.1.
+        pub fn y(&self) -> usize {
+            self.1
+        }
+
+        /// Convenient alias for coord.2.
+        pub fn z(&self) -> usize {
+            self.2
+        }
+
+        /// Measures the volume of the coord.
+        pub fn volume(&self) -> usize {
+            self.0 * self.1 * self.2
+        }
+
+        /// Returns true if each of x, y, and z is less than the corresponding value in bound.
+        pub fn is_within(&self, bound: Coord3) -> bool {
+            self.0 < bound.0 && self.1 < bound.1 && self.2 < bound.2
+        }
+
+        /// This returns an iterator that will go through all the Coord3s that are less than
+        /// this one, changing x fastest, then y, then z.
+        pub fn range(&self) -> impl Iterator<Item=Coord3> {
+            let bound = *self;
+            (0..bound.2).flat_map(move |z| {
+                (0..bound.1).flat_map(move |y| (0..bound.0).map(move |x| Coord3(x, y, z)))
+            })
+        }
+
+        /// This gives the coord that is offset from this one by (dx, dy, dz), or None if
+        /// that would go below 0 or not be within bound.
+        pub fn bounded_offset(&self, (dx, dy, dz): (i64, i64, i64), bound: Coord3) -> Option<Coord3> {
+            let answer = Coord3(
+                self.0.checked_add_signed(dx as isize)?,
+                self.1.checked_add_signed(dy as isize)?,
+                self.2.checked_add_signed(dz as isize)?,
+            );
+            answer.is_within(bound).then_some(answer)
+        }
+
+        /// This returns the neighbors of this coord. It will *not* include any neighbors
+        /// that would have a coordinate < 0, or which would not be within bound.
+        pub fn neighbors(&self, neighborhood: Neighborhood3, bound: Coord3) -> Vec<Coord3> {
+            neighborhood.offsets().iter()
+                .filter_map(|offset| self.bounded_offset(*offset, bound))
+                .collect()
+        }
+
+        /// Returns the taxicab distance between this coord and another.
+        pub fn taxicab_dist(&self, other: Coord3) -> usize {
+            self.0.abs_diff(other.0) + self.1.abs_diff(other.1) + self.2.abs_diff(other.2)
+        }
+    }
+
+    impl Display for Coord3 {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "({},{},{})", self.0, self.1, self.2)
+        }
+    }
+
+    impl Add for Coord3 {
+        type Output = Coord3;
+
+        fn add(self, rhs: Self) -> Self::Output {
+            Coord3(self.0 + rhs.0, self.1 + rhs.1, self.2 + rhs.2)
+        }
+    }
+}
+
+
+// ============================================ ICoord3 =============================================
+
+mod icoord3 {
+    use std::fmt::{Display, Formatter};
+    use std::num::TryFromIntError;
+    use std::ops::{Add, AddAssign, Sub};
+    use super::{Coord3, Neighborhood3};
+
+
+    /// A 3D coordinate that can be negative.
+    #[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, Default)]
+    pub struct ICoord3(pub i64, pub i64, pub i64);
+
+
+    impl ICoord3 {
+        /// Convenient alias for coord.0.
+        pub fn x(&self) -> i64 {
+            self.0
+        }
+
+        /// Convenient alias for coord.1.
+        pub fn y(&self) -> i64 {
+            self.1
+        }
+
+        /// Convenient alias for coord.2.
+        pub fn z(&self) -> i64 {
+            self.2
+        }
+
+        /// This gives the coord that is offset from this one by (dx, dy, dz).
+        pub fn offset(&self, (dx, dy, dz): (i64, i64, i64)) -> ICoord3 {
+            ICoord3(self.0 + dx, self.1 + dy, self.2 + dz)
+        }
+
+        /// This returns an iterator of the neighbors of this coord.
+        pub fn neighbors(&self, neighborhood: Neighborhood3) -> impl Iterator<Item=ICoord3> {
+            let center = *self;
+            neighborhood.offsets().iter().map(move |offset| center.offset(*offset))
+        }
+
+        /// Returns the taxicab distance between this coord and another.
+        pub fn taxicab_dist(&self, other: ICoord3) -> u64 {
+            self.0.abs_diff(other.0) + self.1.abs_diff(other.1) + self.2.abs_diff(other.2)
+        }
+    }
+
+    impl Display for ICoord3 {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "({},{},{})", self.0, self.1, self.2)
+        }
+    }
+
+    impl Add for ICoord3 {
+        type Output = ICoord3;
+
+        fn add(self, rhs: Self) -> Self::Output {
+            ICoord3(self.0 + rhs.0, self.1 + rhs.1, self.2 + rhs.2)
+        }
+    }
+
+    impl AddAssign for ICoord3 {
+        fn add_assign(&mut self, rhs: Self) {
+            *self = *self + rhs;
+        }
+    }
+
+    impl Sub for ICoord3 {
+        type Output = ICoord3;
+
+        fn sub(self, rhs: Self) -> Self::Output {
+            ICoord3(self.0 - rhs.0, self.1 - rhs.1, self.2 - rhs.2)
+        }
+    }
+
+    impl From<Coord3> for ICoord3 {
+        fn from(value: Coord3) -> Self {
+            ICoord3(value.0 as i64, value.1 as i64, value.2 as i64)
+        }
+    }
+
+    /// An ICoord3 can become a Coord3 only if none of its values are negative.
+    impl TryFrom<ICoord3> for Coord3 {
+        type Error = TryFromIntError;
+
+        fn try_from(value: ICoord3) -> Result<Self, Self::Error> {
+            Ok(Coord3(value.0.try_into()?, value.1.try_into()?, value.2.try_into()?))
+        }
+    }
+}
+
+
+// ============================================ BoundingBox3 =============================================
+
+mod bounding_box {
+    use std::fmt::{Display, Formatter};
+    use super::ICoord3;
+
+
+    /// A box-shaped region of space, aligned with the axes. Both min and max are INCLUSIVE,
+    /// so a box always contains at least one point.
+    #[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+    pub struct BoundingBox3 {
+        pub min: ICoord3,
+        pub max: ICoord3,
+    }
+
+    impl BoundingBox3 {
+        /// Creates a box from two opposite corners (in any order).
+        pub fn new(a: ICoord3, b: ICoord3) -> Self {
+            BoundingBox3{
+                min: ICoord3(a.0.min(b.0), a.1.min(b.1), a.2.min(b.2)),
+                max: ICoord3(a.0.max(b.0), a.1.max(b.1), a.2.max(b.2)),
+            }
+        }
+
+        /// Returns the smallest box containing all the points, or None if there are none.
+        pub fn enclosing<I: IntoIterator<Item=ICoord3>>(points: I) -> Option<Self> {
+            let mut iter = points.into_iter();
+            let first = iter.next()?;
+            let mut answer = BoundingBox3{min: first, max: first};
+            for p in iter {
+                answer.include(p);
+            }
+            Some(answer)
+        }
+
+        /// Grows this box (if needed) so it contains the point.
+        pub fn include(&mut self, p: ICoord3) {
+            *self = BoundingBox3::new(
+                ICoord3(self.min.0.min(p.0), self.min.1.min(p.1), self.min.2.min(p.2)),
+                ICoord3(self.max.0.max(p.0), self.max.1.max(p.1), self.max.2.max(p.2)),
+            );
+        }
+
+        /// Returns a box that is larger by margin on every side. A negative margin shrinks
+        /// the box instead; since a box can't be empty, any axis that would shrink past
+        /// nothing is clamped to its middle point.
+        pub fn grow(&self, margin: i64) -> Self {
+            let axis = |lo: i64, hi: i64| {
+                if lo - margin <= hi + margin {
+                    (lo - margin, hi + margin)
+                } else {
+                    let mid = (lo + hi).div_euclid(2);
+                    (mid, mid)
+                }
+            };
+            let (min_x, max_x) = axis(self.min.0, self.max.0);
+            let (min_y, max_y) = axis(self.min.1, self.max.1);
+            let (min_z, max_z) = axis(self.min.2, self.max.2);
+            BoundingBox3{min: ICoord3(min_x, min_y, min_z), max: ICoord3(max_x, max_y, max_z)}
+        }
+
+        /// Returns true if the point is inside (or on the surface of) the box.
+        pub fn contains(&self, p: ICoord3) -> bool {
+            self.min.0 <= p.0 && p.0 <= self.max.0 &&
+                self.min.1 <= p.1 && p.1 <= self.max.1 &&
+                self.min.2 <= p.2 && p.2 <= self.max.2
+        }
+
+        /// Returns the region common to both boxes, or None if they don't overlap.
+        pub fn intersect(&self, other: &Self) -> Option<Self> {
+            let min = ICoord3(self.min.0.max(other.min.0), self.min.1.max(other.min.1), self.min.2.max(other.min.2));
+            let max = ICoord3(self.max.0.min(other.max.0), self.max.1.min(other.max.1), self.max.2.min(other.max.2));
+            (min.0 <= max.0 && min.1 <= max.1 && min.2 <= max.2).then_some(BoundingBox3{min, max})
+        }
+
+        /// Returns the number of points in the box.
+        pub fn volume(&self) -> u64 {
+            (self.min.0.abs_diff(self.max.0) + 1) *
+                (self.min.1.abs_diff(self.max.1) + 1) *
+                (self.min.2.abs_diff(self.max.2) + 1)
+        }
+
+        /// Returns an iterator of every point in the box, changing x fastest, then y, then z.
+        pub fn iter(&self) -> impl Iterator<Item=ICoord3> {
+            let BoundingBox3{min, max} = *self;
+            (min.2..=max.2).flat_map(move |z| {
+                (min.1..=max.1).flat_map(move |y| (min.0..=max.0).map(move |x| ICoord3(x, y, z)))
+            })
+        }
+    }
+
+    impl Display for BoundingBox3 {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}..={}", self.min, self.max)
+        }
+    }
+}
+
+
+// ============================================ Grid3 =============================================
+
+mod dense {
+    use std::collections::VecDeque;
+    use std::fmt::{Debug, Formatter};
+    use super::{Coord3, Neighborhood3};
+
+
+    #[derive(Clone, Eq, PartialEq, Hash)]
+    pub struct Grid3<T> {
+        bound: Coord3,
+        data: Vec<T>,
+    }
+
+
+    impl<T> Grid3<T> {
+        /// Gives the index into data for a coord. Panics if it is out of bounds.
+        fn index(&self, coord: Coord3) -> usize {
+            assert!(coord.is_within(self.bound));
+            (coord.2 * self.bound.1 + coord.1) * self.bound.0 + coord.0
+        }
+
+        /// Gets the value at a location
+        pub fn get(&self, coord: Coord3) -> &T {
+            &self.data[self.index(coord)]
+        }
+
+        /// Gets a mutable reference to the value at a location.
+        pub fn get_mut(&mut self, coord: Coord3) -> &mut T {
+            let idx = self.index(coord);
+            &mut self.data[idx]
+        }
+
+        /// Sets a value at a location.
+        pub fn set(&mut self, coord: Coord3, val: T) {
+            *self.get_mut(coord) = val;
+        }
+
+        /// Returns the bounds of this grid.
+        pub fn bound(&self) -> Coord3 {
+            self.bound
+        }
+
+        /// Given a function that produces individual elements (and a bounds), this creates a
+        /// new Grid3.
+        pub fn from_function<Func>(bound: Coord3, f: Func) -> Self
+            where Func: FnMut(Coord3) -> T
+        {
+            let data: Vec<T> = bound.range().map(f).collect();
+            Self { bound, data }
+        }
+
+        /// Returns an iterator of each coord along with the value at that coord.
+        pub fn iter(&self) -> impl Iterator<Item=(Coord3, &T)> {
+            self.bound.range().zip(self.data.iter())
+        }
+
+        /// Returns all the coords that can be reached from start without leaving the grid,
+        /// moving only onto cells where passable() is true. The start is always included.
+        pub fn flood_fill<P>(&self, start: Coord3, neighborhood: Neighborhood3, passable: P) -> Grid3<bool>
+            where P: Fn(&T) -> bool
+        {
+            let mut reached: Grid3<bool> = Grid3::new_default(self.bound);
+            reached.set(start, true);
+            let mut queue = VecDeque::from([start]);
+            while let Some(coord) = queue.pop_front() {
+                for next in coord.neighbors(neighborhood, self.bound) {
+                    if !*reached.get(next) && passable(self.get(next)) {
+                        reached.set(next, true);
+                        queue.push_back(next);
+                    }
+                }
+            }
+            reached
+        }
+    }
+
+    impl<T: Default> Grid3<T> {
+        /// Create a new Grid3 filled with the default value.
+        pub fn new_default(bound: Coord3) -> Self {
+            Self::from_function(bound, |_| T::default())
+        }
+    }
+
+    impl<T: Debug> Debug for Grid3<T> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            for z in 0..self.bound.2 {
+                writeln!(f, "z={}", z)?;
+                for y in 0..self.bound.1 {
+                    for x in 0..self.bound.0 {
+                        write!(f, "{:?}", self.get(Coord3(x, y, z)))?;
+                    }
+                    writeln!(f)?;
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+
+// ============================================ PointSet3 =============================================
+
+mod point_set {
+    use std::collections::{HashSet, VecDeque};
+    use super::{BoundingBox3, ICoord3, Neighborhood3};
+
+
+    /// A set of points in 3D space. Only the points that are present are stored, so the
+    /// points can be spread over a huge (or negative) range.
+    #[derive(Debug, Clone, Default, Eq, PartialEq)]
+    pub struct PointSet3 {
+        points: HashSet<ICoord3>,
+    }
+
+
+    impl PointSet3 {
+        /// Creates a new, empty PointSet3.
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Adds a point. Returns true if it wasn't already present.
+        pub fn insert(&mut self, p: ICoord3) -> bool {
+            self.points.insert(p)
+        }
+
+        /// Removes a point. Returns true if it was present.
+        pub fn remove(&mut self, p: ICoord3) -> bool {
+            self.points.remove(&p)
+        }
+
+        /// Returns true if the point is present.
+        pub fn contains(&self, p: ICoord3) -> bool {
+            self.points.contains(&p)
+        }
+
+        /// Returns the number of points.
+        pub fn len(&self) -> usize {
+            self.points.len()
+        }
+
+        /// Returns true if there are no points.
+        pub fn is_empty(&self) -> bool {
+            self.points.is_empty()
+        }
+
+        /// Iterates through the points (in no particular order).
+        pub fn iter(&self) -> impl Iterator<Item=ICoord3> + '_ {
+            self.points.iter().copied()
+        }
+
+        /// Returns the smallest box containing every point, or None if there are no points.
+        pub fn bounding_box(&self) -> Option<BoundingBox3> {
+            BoundingBox3::enclosing(self.iter())
+        }
+
+        /// Returns the neighbors of p that are present.
+        pub fn present_neighbors(&self, p: ICoord3, neighborhood: Neighborhood3) -> impl Iterator<Item=ICoord3> + '_ {
+            p.neighbors(neighborhood).filter(|n| self.contains(*n))
+        }
+
+        /// Returns all points that are NOT in this set which can be reached from start by
+        /// moving through points not in this set and without leaving within. If start is
+        /// in the set or outside of within, this returns an empty set.
+        pub fn flood_fill(&self, start: ICoord3, neighborhood: Neighborhood3, within: &BoundingBox3) -> PointSet3 {
+            let mut reached = PointSet3::new();
+            if self.contains(start) || !within.contains(start) {
+                return reached;
+            }
+            reached.insert(start);
+            let mut queue = VecDeque::from([start]);
+            while let Some(p) = queue.pop_front() {
+                for next in p.neighbors(neighborhood) {
+                    if within.contains(next) && !self.contains(next) && reached.insert(next) {
+                        queue.push_back(next);
+                    }
+                }
+            }
+            reached
+        }
+
+        /// Counts the faces of the unit cubes at each point which are not shared with
+        /// another point in the set.
+        pub fn exposed_faces(&self) -> usize {
+            self.iter()
+                .map(|p| p.neighbors(Neighborhood3::Six).filter(|n| !self.contains(*n)).count())
+                .sum()
+        }
+
+        /// Like exposed_faces(), but does not count faces that face into a pocket which is
+        /// completely enclosed by the set.
+        pub fn exterior_faces(&self) -> usize {
+            let Some(bounding_box) = self.bounding_box() else {
+                return 0;
+            };
+            let within = bounding_box.grow(1);
+            let outside = self.flood_fill(within.min, Neighborhood3::Six, &within);
+            self.iter()
+                .map(|p| p.neighbors(Neighborhood3::Six).filter(|n| outside.contains(*n)).count())
+                .sum()
+        }
+    }
+
+    impl FromIterator<ICoord3> for PointSet3 {
+        fn from_iter<I: IntoIterator<Item=ICoord3>>(iter: I) -> Self {
+            PointSet3{points: iter.into_iter().collect()}
+        }
+    }
+
+    impl Extend<ICoord3> for PointSet3 {
+        fn extend<I: IntoIterator<Item=ICoord3>>(&mut self, iter: I) {
+            self.points.extend(iter)
+        }
+    }
+}
+
+
+// ========================== TESTS ==========================
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_neighborhoods() {
+        assert_eq!(ICoord3(0, 0, 0).neighbors(Neighborhood3::TwentySix).count(), 26);
+        assert!(!ICoord3(5, 5, 5).neighbors(Neighborhood3::TwentySix).any(|n| n == ICoord3(5, 5, 5)));
+        assert_eq!(Coord3(0, 0, 0).neighbors(Neighborhood3::Six, Coord3(2, 2, 2)).len(), 3);
+        assert_eq!(Coord3(0, 0, 0).neighbors(Neighborhood3::TwentySix, Coord3(2, 2, 2)).len(), 7);
+        assert_eq!(Coord3(1, 1, 1).neighbors(Neighborhood3::TwentySix, Coord3(3, 3, 3)).len(), 26);
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let bb = BoundingBox3::enclosing([ICoord3(1, -2, 3), ICoord3(-1, 4, 0)]).unwrap();
+        assert_eq!(bb, BoundingBox3{min: ICoord3(-1, -2, 0), max: ICoord3(1, 4, 3)});
+        assert_eq!(bb.volume(), 3 * 7 * 4);
+        assert_eq!(bb.iter().count() as u64, bb.volume());
+        let other = BoundingBox3::new(ICoord3(1, 4, 3), ICoord3(9, 9, 9));
+        assert_eq!(bb.intersect(&other).unwrap().volume(), 1);
+        assert_eq!(bb.intersect(&other.grow(-1)), None);
+        let unit = BoundingBox3{min: ICoord3(0, 0, 0), max: ICoord3(0, 0, 0)};
+        assert_eq!(unit.grow(-2), unit);
+        assert_eq!(bb.grow(-2), BoundingBox3{min: ICoord3(0, 0, 1), max: ICoord3(0, 2, 1)});
+    }
+
+    #[test]
+    fn test_surface_area() {
+        // The example from Advent of Code 2022 day 18.
+        let cubes: PointSet3 = [
+            (2,2,2), (1,2,2), (3,2,2), (2,1,2), (2,3,2), (2,2,1), (2,2,3),
+            (2,2,4), (2,2,6), (1,2,5), (3,2,5), (2,1,5), (2,3,5),
+        ].into_iter().map(|(x, y, z)| ICoord3(x, y, z)).collect();
+        assert_eq!(cubes.exposed_faces(), 64);
+        assert_eq!(cubes.exterior_faces(), 58);
+    }
+
+    #[test]
+    fn test_grid3_flood_fill() {
+        // a wall at x == 1 splits the grid in two, except for a hole at (1,2,2)
+        let grid = Grid3::from_function(Coord3(3, 3, 3), |c| c.0 == 1 && c != Coord3(1, 2, 2));
+        let reached = grid.flood_fill(Coord3(0, 0, 0), Neighborhood3::Six, |wall| !wall);
+        assert_eq!(reached.iter().filter(|(_, r)| **r).count(), 19);
+        let reached = grid.flood_fill(Coord3(0, 0, 0), Neighborhood3::TwentySix, |wall| !wall);
+        assert!(*reached.get(Coord3(2, 2, 2)));
+    }
+}
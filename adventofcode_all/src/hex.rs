@@ -0,0 +1,433 @@
+//! Hexagonal grids. A Hex is stored in axial coordinates (q, r); the third cube coordinate
+//! s is always -q - r. Hexes can be laid out "pointy-top" (neighbors are e, se, sw, w, nw,
+//! ne) or "flat-top" (neighbors are n, ne, se, s, sw, nw). The coordinates work the same
+//! either way, so there is one HexDirection (for pointy-top) and a FlatHexDirection that
+//! converts into it.
+
+pub use direction::{HexDirection, FlatHexDirection, HexParseError, parse_hex_path};
+pub use hex_coord::Hex;
+pub use hex_map::HexMap;
+
+
+// ============================================ Direction =============================================
+
+mod direction {
+    use std::error::Error;
+    use std::fmt::{Display, Formatter};
+    use std::str::FromStr;
+
+
+    /// The six directions between neighboring hexes in a pointy-top layout.
+    #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+    pub enum HexDirection {
+        East, SouthEast, SouthWest, West, NorthWest, NorthEast
+    }
+
+    /// The six directions between neighboring hexes in a flat-top layout.
+    #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+    pub enum FlatHexDirection {
+        North, NorthEast, SouthEast, South, SouthWest, NorthWest
+    }
+
+    /// An error for a string that isn't a valid hex direction.
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    pub struct HexParseError(pub String);
+
+    impl Display for HexParseError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "invalid hex direction '{}'", self.0)
+        }
+    }
+
+    impl Error for HexParseError {}
+
+
+    impl HexDirection {
+        /// All six directions, going clockwise starting from East.
+        pub const ALL: [HexDirection; 6] = [
+            HexDirection::East, HexDirection::SouthEast, HexDirection::SouthWest,
+            HexDirection::West, HexDirection::NorthWest, HexDirection::NorthEast,
+        ];
+
+        /// Returns the position of this in ALL.
+        fn index(&self) -> usize {
+            *self as usize
+        }
+
+        /// Returns the next HexDirection clockwise from this one (a 60 degree turn).
+        pub fn clockwise(&self) -> HexDirection {
+            Self::ALL[(self.index() + 1) % 6]
+        }
+
+        /// Returns the next HexDirection counter-clockwise from this one (a 60 degree turn).
+        pub fn counter_clockwise(&self) -> HexDirection {
+            Self::ALL[(self.index() + 5) % 6]
+        }
+
+        /// Returns the opposite HexDirection.
+        pub fn reverse(&self) -> HexDirection {
+            Self::ALL[(self.index() + 3) % 6]
+        }
+
+        /// Returns the (dq, dr) of one step in this direction.
+        pub fn offset(&self) -> (i64, i64) {
+            match self {
+                HexDirection::East => (1, 0),
+                HexDirection::SouthEast => (0, 1),
+                HexDirection::SouthWest => (-1, 1),
+                HexDirection::West => (-1, 0),
+                HexDirection::NorthWest => (0, -1),
+                HexDirection::NorthEast => (1, -1),
+            }
+        }
+    }
+
+    impl FlatHexDirection {
+        /// All six directions, going clockwise starting from North.
+        pub const ALL: [FlatHexDirection; 6] = [
+            FlatHexDirection::North, FlatHexDirection::NorthEast, FlatHexDirection::SouthEast,
+            FlatHexDirection::South, FlatHexDirection::SouthWest, FlatHexDirection::NorthWest,
+        ];
+    }
+
+    /// Turning the layout by 30 degrees makes each flat-top direction line up with a
+    /// pointy-top one that has the same (dq, dr).
+    impl From<FlatHexDirection> for HexDirection {
+        fn from(value: FlatHexDirection) -> Self {
+            match value {
+                FlatHexDirection::North => HexDirection::NorthWest,
+                FlatHexDirection::NorthEast => HexDirection::NorthEast,
+                FlatHexDirection::SouthEast => HexDirection::East,
+                FlatHexDirection::South => HexDirection::SouthEast,
+                FlatHexDirection::SouthWest => HexDirection::SouthWest,
+                FlatHexDirection::NorthWest => HexDirection::West,
+            }
+        }
+    }
+
+    /// Parses "e", "se", "sw", "w", "nw", or "ne" (in either case).
+    impl FromStr for HexDirection {
+        type Err = HexParseError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s.to_ascii_lowercase().as_str() {
+                "e" => Ok(HexDirection::East),
+                "se" => Ok(HexDirection::SouthEast),
+                "sw" => Ok(HexDirection::SouthWest),
+                "w" => Ok(HexDirection::West),
+                "nw" => Ok(HexDirection::NorthWest),
+                "ne" => Ok(HexDirection::NorthEast),
+                _ => Err(HexParseError(s.to_string())),
+            }
+        }
+    }
+
+    /// Parses "n", "ne", "se", "s", "sw", or "nw" (in either case).
+    impl FromStr for FlatHexDirection {
+        type Err = HexParseError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s.to_ascii_lowercase().as_str() {
+                "n" => Ok(FlatHexDirection::North),
+                "ne" => Ok(FlatHexDirection::NorthEast),
+                "se" => Ok(FlatHexDirection::SouthEast),
+                "s" => Ok(FlatHexDirection::South),
+                "sw" => Ok(FlatHexDirection::SouthWest),
+                "nw" => Ok(FlatHexDirection::NorthWest),
+                _ => Err(HexParseError(s.to_string())),
+            }
+        }
+    }
+
+    impl Display for HexDirection {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", match self {
+                HexDirection::East => "E",
+                HexDirection::SouthEast => "SE",
+                HexDirection::SouthWest => "SW",
+                HexDirection::West => "W",
+                HexDirection::NorthWest => "NW",
+                HexDirection::NorthEast => "NE",
+            })
+        }
+    }
+
+    impl Display for FlatHexDirection {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", match self {
+                FlatHexDirection::North => "N",
+                FlatHexDirection::NorthEast => "NE",
+                FlatHexDirection::SouthEast => "SE",
+                FlatHexDirection::South => "S",
+                FlatHexDirection::SouthWest => "SW",
+                FlatHexDirection::NorthWest => "NW",
+            })
+        }
+    }
+
+    /// Parses pointy-top directions that are run together with no separator, like
+    /// "esenee" (which is e, se, ne, e).
+    pub fn parse_hex_path(s: &str) -> Result<Vec<HexDirection>, HexParseError> {
+        let mut answer = Vec::new();
+        let mut rest = s.trim();
+        while !rest.is_empty() {
+            let len = if rest.starts_with(['n', 's', 'N', 'S']) {2} else {1};
+            let split = rest.char_indices().nth(len).map_or(rest.len(), |(i, _)| i);
+            let (token, remaining) = rest.split_at(split);
+            answer.push(token.parse()?);
+            rest = remaining;
+        }
+        Ok(answer)
+    }
+}
+
+
+// ============================================ Hex =============================================
+
+mod hex_coord {
+    use std::fmt::{Display, Formatter};
+    use std::ops::{Add, AddAssign, Sub};
+    use super::HexDirection;
+
+
+    /// A location on a hex grid, in axial coordinates (q, r).
+    #[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, Default)]
+    pub struct Hex(pub i64, pub i64);
+
+
+    impl Hex {
+        /// Creates a Hex from cube coordinates. Panics if q + r + s != 0.
+        pub fn from_cube(q: i64, r: i64, s: i64) -> Self {
+            assert_eq!(q + r + s, 0, "cube coordinates must sum to zero");
+            Hex(q, r)
+        }
+
+        /// Convenient alias for hex.0.
+        pub fn q(&self) -> i64 {
+            self.0
+        }
+
+        /// Convenient alias for hex.1.
+        pub fn r(&self) -> i64 {
+            self.1
+        }
+
+        /// Returns the third cube coordinate.
+        pub fn s(&self) -> i64 {
+            -self.0 - self.1
+        }
+
+        /// This gives the hex that is one step away in the given direction. It accepts
+        /// either a HexDirection or a FlatHexDirection.
+        pub fn step<D: Into<HexDirection>>(&self, dir: D) -> Hex {
+            let (dq, dr) = dir.into().offset();
+            Hex(self.0 + dq, self.1 + dr)
+        }
+
+        /// This gives the hex that is n steps away in the given direction.
+        pub fn step_n<D: Into<HexDirection>>(&self, dir: D, n: i64) -> Hex {
+            let (dq, dr) = dir.into().offset();
+            Hex(self.0 + dq * n, self.1 + dr * n)
+        }
+
+        /// This returns the six neighbors of this hex, going clockwise from East.
+        pub fn neighbors(&self) -> [Hex; 6] {
+            HexDirection::ALL.map(|dir| self.step(dir))
+        }
+
+        /// Returns the number of steps it takes to get from this hex to the other.
+        pub fn distance(&self, other: Hex) -> u64 {
+            let diff = *self - other;
+            (diff.0.unsigned_abs() + diff.1.unsigned_abs() + diff.s().unsigned_abs()) / 2
+        }
+
+        /// Returns the hexes that are exactly radius steps from this one, going around
+        /// clockwise. A radius of 0 gives just this hex.
+        pub fn ring(&self, radius: u64) -> Vec<Hex> {
+            if radius == 0 {
+                return vec![*self];
+            }
+            let radius = radius as i64;
+            let mut answer = Vec::with_capacity(6 * radius as usize);
+            let mut pos = self.step_n(HexDirection::East, radius);
+            let mut dir = HexDirection::SouthWest;
+            for _ in 0..6 {
+                for _ in 0..radius {
+                    answer.push(pos);
+                    pos = pos.step(dir);
+                }
+                dir = dir.clockwise();
+            }
+            answer
+        }
+
+        /// Returns all the hexes within radius steps of this one: this hex first, then
+        /// each ring going outward.
+        pub fn spiral(&self, radius: u64) -> Vec<Hex> {
+            (0..=radius).flat_map(|r| self.ring(r)).collect()
+        }
+    }
+
+    impl Display for Hex {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "({},{})", self.0, self.1)
+        }
+    }
+
+    impl Add for Hex {
+        type Output = Hex;
+
+        fn add(self, rhs: Self) -> Self::Output {
+            Hex(self.0 + rhs.0, self.1 + rhs.1)
+        }
+    }
+
+    impl AddAssign for Hex {
+        fn add_assign(&mut self, rhs: Self) {
+            *self = *self + rhs;
+        }
+    }
+
+    impl Sub for Hex {
+        type Output = Hex;
+
+        fn sub(self, rhs: Self) -> Self::Output {
+            Hex(self.0 - rhs.0, self.1 - rhs.1)
+        }
+    }
+}
+
+
+// ============================================ HexMap =============================================
+
+mod hex_map {
+    use std::collections::HashMap;
+    use super::Hex;
+
+    /// A hex grid with no edges: any Hex can be used, and cells that have never been set
+    /// hold a default value.
+    #[derive(Debug, Clone)]
+    pub struct HexMap<T> {
+        default: T,
+        data: HashMap<Hex, T>,
+    }
+
+    impl<T> HexMap<T> {
+        /// Creates an empty HexMap where every cell has the default value.
+        pub fn new(default: T) -> Self {
+            HexMap{default, data: HashMap::new()}
+        }
+
+        /// Gets the value at a location.
+        pub fn get(&self, hex: Hex) -> &T {
+            self.data.get(&hex).unwrap_or(&self.default)
+        }
+
+        /// Returns true if the location has been set (even if it was set to the default).
+        pub fn contains(&self, hex: Hex) -> bool {
+            self.data.contains_key(&hex)
+        }
+
+        /// Sets a value at a location.
+        pub fn set(&mut self, hex: Hex, val: T) {
+            self.data.insert(hex, val);
+        }
+
+        /// Removes the value at a location (so it goes back to the default), returning the
+        /// value that was there if it had been set.
+        pub fn remove(&mut self, hex: Hex) -> Option<T> {
+            self.data.remove(&hex)
+        }
+
+        /// Returns the number of locations that have been set.
+        pub fn len(&self) -> usize {
+            self.data.len()
+        }
+
+        /// Returns true if no locations have been set.
+        pub fn is_empty(&self) -> bool {
+            self.data.is_empty()
+        }
+
+        /// Iterate over the locations that have been set and their values, in no
+        /// particular order.
+        pub fn iter(&self) -> impl Iterator<Item=(Hex, &T)> {
+            self.data.iter().map(|(h, v)| (*h, v))
+        }
+
+        /// Returns how many of the neighbors of hex have a value for which pred() is true.
+        pub fn count_neighbors<P: Fn(&T) -> bool>(&self, hex: Hex, pred: P) -> usize {
+            hex.neighbors().iter().filter(|n| pred(self.get(**n))).count()
+        }
+    }
+
+    impl<T: Clone> HexMap<T> {
+        /// Gets a mutable reference to the value at a location, setting it to the default
+        /// first if it had not been set.
+        pub fn get_mut(&mut self, hex: Hex) -> &mut T {
+            let default = &self.default;
+            self.data.entry(hex).or_insert_with(|| default.clone())
+        }
+    }
+}
+
+
+
+// ========================== TESTS ==========================
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn flat_walk(path: &str) -> Hex {
+        path.split(',')
+            .map(|s| s.parse::<FlatHexDirection>().unwrap())
+            .fold(Hex(0, 0), |pos, dir| pos.step(dir))
+    }
+
+    #[test]
+    fn test_flat_distance() {
+        // The examples from Advent of Code 2017 day 11.
+        let origin = Hex(0, 0);
+        assert_eq!(flat_walk("ne,ne,ne").distance(origin), 3);
+        assert_eq!(flat_walk("ne,ne,sw,sw").distance(origin), 0);
+        assert_eq!(flat_walk("ne,ne,s,s").distance(origin), 2);
+        assert_eq!(flat_walk("se,sw,se,sw,sw").distance(origin), 3);
+    }
+
+    #[test]
+    fn test_parse_hex_path() {
+        // From Advent of Code 2020 day 24.
+        let path = parse_hex_path("esenee").unwrap();
+        assert_eq!(path, vec![HexDirection::East, HexDirection::SouthEast, HexDirection::NorthEast, HexDirection::East]);
+        let end = parse_hex_path("nwwswee").unwrap().into_iter().fold(Hex(0, 0), |pos, dir| pos.step(dir));
+        assert_eq!(end, Hex(0, 0));
+        assert_eq!(parse_hex_path("enx"), Err(HexParseError("nx".to_string())));
+        assert_eq!(parse_hex_path("é"), Err(HexParseError("é".to_string())));
+        assert_eq!(parse_hex_path("enä"), Err(HexParseError("nä".to_string())));
+    }
+
+    #[test]
+    fn test_ring_and_spiral() {
+        let center = Hex(2, -1);
+        let mut ring1 = center.ring(1);
+        let mut neighbors = center.neighbors().to_vec();
+        ring1.sort_by_key(|h| (h.0, h.1));
+        neighbors.sort_by_key(|h| (h.0, h.1));
+        assert_eq!(ring1, neighbors);
+        let ring3 = center.ring(3);
+        assert_eq!(ring3.len(), 18);
+        assert!(ring3.iter().all(|h| h.distance(center) == 3));
+        assert!(ring3.windows(2).all(|w| w[0].distance(w[1]) == 1));
+        assert_eq!(center.spiral(3).len(), 1 + 3 * 3 * 4);
+    }
+
+    #[test]
+    fn test_hex_map() {
+        let mut map = HexMap::new(false);
+        map.set(Hex(1, 0), true);
+        *map.get_mut(Hex(0, 1)) = true;
+        assert_eq!(map.count_neighbors(Hex(0, 0), |b| *b), 2);
+        assert_eq!(map.len(), 2);
+    }
+}
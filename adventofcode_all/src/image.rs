@@ -0,0 +1,248 @@
+//! Exports grids as images. For a big map, a picture (where each cell becomes a block of
+//! colored pixels) is much easier to look at than pages of text. Images can be written as
+//! PPM (which nearly any image viewer can open) or PNG. The PNG encoder is a tiny one that
+//! doesn't compress, which keeps it short and needs no extra crates.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use crate::grid::{Coord, Grid};
+
+
+/// A color, as red, green, and blue.
+pub type Rgb = [u8; 3];
+
+/// An image made from a Grid, with each cell drawn as a scale-by-scale square of pixels.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GridImage {
+    scale: usize,
+    width: usize, // in pixels
+    height: usize, // in pixels
+    pixels: Vec<Rgb>,
+}
+
+
+impl GridImage {
+    /// Creates an image of the grid, where palette() gives the color of each cell and each
+    /// cell becomes a square of scale by scale pixels. Panics if scale is 0.
+    pub fn from_grid<T, F>(grid: &Grid<T>, scale: usize, palette: F) -> Self
+        where F: Fn(&T) -> Rgb
+    {
+        assert!(scale > 0, "scale must be at least 1");
+        let bound = grid.bound();
+        let (width, height) = (bound.0 * scale, bound.1 * scale);
+        let pixels = (0..height)
+            .flat_map(|py| (0..width).map(move |px| Coord(px / scale, py / scale)))
+            .map(|c| palette(grid.get(c)))
+            .collect();
+        GridImage{scale, width, height, pixels}
+    }
+
+    /// Returns the width of the image in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the image in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the color of a pixel. Panics if (x, y) is outside the image.
+    pub fn pixel(&self, x: usize, y: usize) -> Rgb {
+        assert!(
+            x < self.width && y < self.height,
+            "Pixel ({},{}) is outside the image size ({},{})", x, y, self.width, self.height,
+        );
+        self.pixels[y * self.width + x]
+    }
+
+    /// Colors in the cell at coord (all of its pixels, at the current scale). Panics if
+    /// coord is outside the grid the image was made from.
+    pub fn paint_cell(&mut self, coord: Coord, color: Rgb) {
+        assert!(
+            coord.0 * self.scale < self.width && coord.1 * self.scale < self.height,
+            "Coord {} is outside the grid bound {}", coord, Coord(self.width / self.scale, self.height / self.scale),
+        );
+        for py in coord.1 * self.scale .. (coord.1 + 1) * self.scale {
+            for px in coord.0 * self.scale .. (coord.0 + 1) * self.scale {
+                self.pixels[py * self.width + px] = color;
+            }
+        }
+    }
+
+    /// Draws a path over the image by painting each of its cells in color. Panics if the
+    /// path leaves the grid.
+    pub fn overlay_path(&mut self, path: &[Coord], color: Rgb) {
+        for coord in path {
+            self.paint_cell(*coord, color);
+        }
+    }
+
+    /// Writes the image in binary PPM (P6) format.
+    pub fn write_ppm<W: Write>(&self, mut out: W) -> std::io::Result<()> {
+        write!(out, "P6\n{} {}\n255\n", self.width, self.height)?;
+        out.write_all(&self.pixels.concat())
+    }
+
+    /// Writes the image in PNG format.
+    pub fn write_png<W: Write>(&self, mut out: W) -> std::io::Result<()> {
+        out.write_all(&[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'])?;
+
+        let mut header = Vec::with_capacity(13);
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        header.extend([8, 2, 0, 0, 0]); // 8 bits per channel, RGB, default methods, no interlace
+        write_png_chunk(&mut out, b"IHDR", &header)?;
+
+        // each row starts with a filter type byte, and we always use 0 (no filter)
+        let mut raw = Vec::with_capacity(self.height * (1 + 3 * self.width));
+        for row in self.pixels.chunks(self.width.max(1)) {
+            raw.push(0);
+            raw.extend(row.concat());
+        }
+        write_png_chunk(&mut out, b"IDAT", &zlib_stored(&raw))?;
+        write_png_chunk(&mut out, b"IEND", &[])
+    }
+
+    /// Writes the image to a file in PPM format.
+    pub fn save_ppm<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write_ppm(&mut out)?;
+        out.flush()
+    }
+
+    /// Writes the image to a file in PNG format.
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write_png(&mut out)?;
+        out.flush()
+    }
+}
+
+
+impl<T> Grid<T> {
+    /// Convenience for GridImage::from_grid().
+    pub fn to_image<F: Fn(&T) -> Rgb>(&self, scale: usize, palette: F) -> GridImage {
+        GridImage::from_grid(self, scale, palette)
+    }
+}
+
+
+/// Writes one PNG chunk: length, type, data, then a CRC of the type and data.
+fn write_png_chunk<W: Write>(out: &mut W, kind: &[u8; 4], data: &[u8]) -> std::io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    let crc = crc32(kind.iter().chain(data.iter()).copied());
+    out.write_all(&crc.to_be_bytes())
+}
+
+/// Wraps data in a zlib stream made of "stored" (uncompressed) deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 65535;
+    let mut answer = vec![0x78, 0x01]; // deflate, 32K window, no preset dictionary
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        answer.extend([1, 0, 0, 0xff, 0xff]); // a single empty final block
+    }
+    while let Some(block) = blocks.next() {
+        let is_final = blocks.peek().is_none();
+        let len = block.len() as u16;
+        answer.push(if is_final {1} else {0});
+        answer.extend(len.to_le_bytes());
+        answer.extend((!len).to_le_bytes());
+        answer.extend(block);
+    }
+    answer.extend(adler32(data).to_be_bytes());
+    answer
+}
+
+/// The CRC-32 checksum used by PNG.
+fn crc32<I: IntoIterator<Item=u8>>(bytes: I) -> u32 {
+    let mut crc = u32::MAX;
+    for byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {(crc >> 1) ^ 0xedb88320} else {crc >> 1};
+        }
+    }
+    !crc
+}
+
+/// The Adler-32 checksum used by zlib.
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % MOD;
+        b = (b + a) % MOD;
+    }
+    (b << 16) | a
+}
+
+
+
+// ========================== TESTS ==========================
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const BLACK: Rgb = [0, 0, 0];
+    const WHITE: Rgb = [255, 255, 255];
+    const RED: Rgb = [255, 0, 0];
+
+    fn checkerboard() -> GridImage {
+        let grid = Grid::from_function(Coord(3, 2), |c| (c.0 + c.1) % 2 == 0);
+        grid.to_image(2, |b| if *b {WHITE} else {BLACK})
+    }
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(*b"123456789"), 0xcbf43926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+    }
+
+    #[test]
+    fn test_scale_and_overlay() {
+        let mut image = checkerboard();
+        assert_eq!((image.width(), image.height()), (6, 4));
+        assert_eq!(image.pixel(1, 1), WHITE);
+        assert_eq!(image.pixel(2, 1), BLACK);
+        image.overlay_path(&[Coord(1, 0)], RED);
+        assert_eq!(image.pixel(3, 1), RED);
+        assert_eq!(image.pixel(4, 1), WHITE);
+    }
+
+    #[test]
+    #[should_panic(expected = "Coord (3,0) is outside the grid bound (3,2)")]
+    fn test_overlay_off_grid() {
+        checkerboard().overlay_path(&[Coord(3, 0)], RED);
+    }
+
+    #[test]
+    #[should_panic(expected = "Pixel (6,0) is outside the image size (6,4)")]
+    fn test_pixel_off_image() {
+        checkerboard().pixel(6, 0); // would otherwise wrap around to (0,1)
+    }
+
+    #[test]
+    fn test_ppm() {
+        let mut out = Vec::new();
+        checkerboard().write_ppm(&mut out).unwrap();
+        assert!(out.starts_with(b"P6\n6 4\n255\n"));
+        assert_eq!(out.len(), 11 + 6 * 4 * 3);
+    }
+
+    #[test]
+    fn test_png() {
+        let mut out = Vec::new();
+        checkerboard().write_png(&mut out).unwrap();
+        assert_eq!(&out[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&out[12..16], b"IHDR");
+        assert_eq!(&out[16..24], &[0, 0, 0, 6, 0, 0, 0, 4]);
+        // the IEND chunk always has the same bytes
+        assert_eq!(&out[out.len() - 12..], &[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]);
+    }
+}
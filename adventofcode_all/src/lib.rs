@@ -7,4 +7,12 @@ pub mod hash_life;
 
 pub mod astar;
 pub mod grid;
+pub mod grid3;
+pub mod hex;
+pub mod image;
+pub mod playback;
+pub mod cube;
+pub mod tsp;
+pub mod geometry;
+pub mod compress;
 pub mod asciienum;
\ No newline at end of file
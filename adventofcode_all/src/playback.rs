@@ -0,0 +1,342 @@
+//! Records the states of a grid simulation so they can be watched afterward. Playing back
+//! in a terminal draws each frame in place (with ANSI colors) and reads simple commands
+//! from stdin to pause, step, or change speed. We don't put the terminal in raw mode, so
+//! each command is typed followed by Enter. When stdout isn't a terminal (eg: the output is
+//! being piped to a file) the frames are written as plain text to a file instead.
+
+use std::fs::File;
+use std::io::{BufRead, BufWriter, IsTerminal, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::Duration;
+use crate::grid::{Coord, Grid};
+use crate::image::Rgb;
+
+
+/// How to draw a cell: the character to show and (optionally) the color to show it in.
+pub type CellStyle = (char, Option<Rgb>);
+
+/// Collects frames of a simulation.
+#[derive(Debug, Clone, Default)]
+pub struct FrameRecorder<T> {
+    frames: Vec<(Grid<T>, String)>,
+}
+
+/// Settings for FrameRecorder::play().
+#[derive(Debug, Clone)]
+pub struct PlaybackOptions {
+    /// How long each frame is shown while playing.
+    pub delay: Duration,
+    /// Whether to start out paused on the first frame.
+    pub start_paused: bool,
+    /// Where to write the frames if stdout is not a terminal.
+    pub fallback_file: PathBuf,
+}
+
+/// A command typed by the user during playback.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Command {
+    TogglePause,
+    StepForward,
+    StepBack,
+    Faster,
+    Slower,
+    Quit,
+}
+
+/// Where playback is: which frame, whether it is paused, and how fast it goes.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PlaybackState {
+    pub index: usize,
+    pub paused: bool,
+    pub delay: Duration,
+    pub num_frames: usize,
+}
+
+
+impl Default for PlaybackOptions {
+    fn default() -> Self {
+        PlaybackOptions{
+            delay: Duration::from_millis(100),
+            start_paused: false,
+            fallback_file: PathBuf::from("playback.txt"),
+        }
+    }
+}
+
+
+impl Command {
+    /// Reads a command from a line of input: an empty line (or "p") toggles pause, "n"
+    /// steps forward, "b" steps back, "+" and "-" change the speed, and "q" quits.
+    /// Returns None for anything else.
+    pub fn parse(line: &str) -> Option<Command> {
+        match line.trim() {
+            "" | "p" => Some(Command::TogglePause),
+            "n" => Some(Command::StepForward),
+            "b" => Some(Command::StepBack),
+            "+" => Some(Command::Faster),
+            "-" => Some(Command::Slower),
+            "q" => Some(Command::Quit),
+            _ => None,
+        }
+    }
+}
+
+
+impl PlaybackState {
+    const MIN_DELAY: Duration = Duration::from_millis(10);
+    const MAX_DELAY: Duration = Duration::from_secs(5);
+
+    /// Creates a PlaybackState at the first frame.
+    pub fn new(num_frames: usize, options: &PlaybackOptions) -> Self {
+        PlaybackState{index: 0, paused: options.start_paused, delay: options.delay, num_frames}
+    }
+
+    /// Updates the state for a command. Stepping pauses playback. Returns false if the
+    /// command was Quit.
+    pub fn apply(&mut self, command: Command) -> bool {
+        match command {
+            Command::TogglePause => self.paused = !self.paused,
+            Command::StepForward => {
+                self.paused = true;
+                self.advance();
+            }
+            Command::StepBack => {
+                self.paused = true;
+                self.index = self.index.saturating_sub(1);
+            }
+            Command::Faster => self.delay = (self.delay / 2).max(Self::MIN_DELAY),
+            Command::Slower => self.delay = (self.delay * 2).min(Self::MAX_DELAY),
+            Command::Quit => return false,
+        }
+        true
+    }
+
+    /// Moves to the next frame (if there is one).
+    fn advance(&mut self) {
+        if self.index + 1 < self.num_frames {
+            self.index += 1;
+        }
+    }
+
+    /// Called when the delay has passed: moves on a frame if we are playing, pausing once
+    /// the last frame is reached.
+    pub fn tick(&mut self) {
+        if !self.paused {
+            self.advance();
+            if self.index + 1 >= self.num_frames {
+                self.paused = true;
+            }
+        }
+    }
+
+    /// Applies any commands waiting on the receiver. Returns false if playback should
+    /// stop: either the user quit, or stdin has closed while paused (so nothing could
+    /// ever resume it). Once stdin closes, playback that is running still plays through
+    /// to the last frame, which pauses it.
+    fn apply_pending(&mut self, receiver: &Receiver<Command>) -> bool {
+        loop {
+            match receiver.try_recv() {
+                Ok(command) => {
+                    if !self.apply(command) {
+                        return false;
+                    }
+                }
+                Err(TryRecvError::Empty) => return true,
+                Err(TryRecvError::Disconnected) => return !self.paused,
+            }
+        }
+    }
+}
+
+
+impl<T: Clone> FrameRecorder<T> {
+    /// Creates an empty FrameRecorder.
+    pub fn new() -> Self {
+        FrameRecorder{frames: Vec::new()}
+    }
+
+    /// Adds a copy of the grid as the next frame.
+    pub fn record(&mut self, grid: &Grid<T>) {
+        self.record_with_caption(grid, String::new());
+    }
+
+    /// Adds a copy of the grid as the next frame, with a caption to show under it.
+    pub fn record_with_caption<S: Into<String>>(&mut self, grid: &Grid<T>, caption: S) {
+        self.frames.push((grid.clone(), caption.into()));
+    }
+}
+
+impl<T> FrameRecorder<T> {
+    /// Returns the number of frames recorded.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns true if no frames have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Renders one frame as text. If color is true, cells that have a color get ANSI
+    /// escape codes.
+    pub fn render_frame<F>(&self, index: usize, style: F, color: bool) -> String
+        where F: Fn(&T) -> CellStyle
+    {
+        let (grid, caption) = &self.frames[index];
+        let mut answer = String::new();
+        for y in 0..grid.bound().1 {
+            for x in 0..grid.bound().0 {
+                match style(grid.get(Coord(x, y))) {
+                    (ch, Some([r, g, b])) if color => {
+                        answer += &format!("\x1b[38;2;{};{};{}m{}\x1b[0m", r, g, b, ch);
+                    }
+                    (ch, _) => answer.push(ch),
+                }
+            }
+            answer.push('\n');
+        }
+        if !caption.is_empty() {
+            answer += caption;
+            answer.push('\n');
+        }
+        answer
+    }
+
+    /// Writes every frame as plain text, each after a header line giving its number.
+    pub fn write_frames<W, F>(&self, mut out: W, style: F) -> std::io::Result<()>
+        where W: Write, F: Fn(&T) -> CellStyle
+    {
+        for index in 0..self.len() {
+            writeln!(out, "=== Frame {} of {} ===", index + 1, self.len())?;
+            write!(out, "{}", self.render_frame(index, &style, false))?;
+        }
+        Ok(())
+    }
+
+    /// Plays the frames. If stdout is a terminal, this animates them and reads commands
+    /// (see Command::parse()) from stdin until the user quits, or until stdin is closed and
+    /// playback is paused. Otherwise, it writes them to options.fallback_file.
+    pub fn play<F>(&self, options: &PlaybackOptions, style: F) -> std::io::Result<()>
+        where F: Fn(&T) -> CellStyle
+    {
+        if !std::io::stdout().is_terminal() {
+            let mut out = BufWriter::new(File::create(&options.fallback_file)?);
+            self.write_frames(&mut out, style)?;
+            return out.flush();
+        }
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        // stdin blocks, so read it on another thread and pass the commands back
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for line in std::io::stdin().lock().lines() {
+                let Ok(line) = line else { break };
+                if let Some(command) = Command::parse(&line) {
+                    if sender.send(command).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        let mut stdout = std::io::stdout().lock();
+        let mut state = PlaybackState::new(self.len(), options);
+        write!(stdout, "\x1b[2J")?; // clear the screen
+        loop {
+            let keep_going = state.apply_pending(&receiver);
+            // clear the rest of each line (and the screen after), in case the last frame was bigger
+            let frame = self.render_frame(state.index, &style, true).replace('\n', "\x1b[K\n");
+            write!(stdout, "\x1b[H{}", frame)?;
+            writeln!(
+                stdout,
+                "\x1b[KFrame {}/{} {} ({} ms)  [Enter: pause/play, n: next, b: back, +/-: speed, q: quit]\x1b[J",
+                state.index + 1, state.num_frames, if state.paused {"paused"} else {"playing"}, state.delay.as_millis(),
+            )?;
+            stdout.flush()?;
+            if !keep_going {
+                return Ok(());
+            }
+            thread::sleep(state.delay);
+            state.tick();
+        }
+    }
+}
+
+
+
+// ========================== TESTS ==========================
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn style(b: &bool) -> CellStyle {
+        if *b {('#', Some([255, 0, 0]))} else {('.', None)}
+    }
+
+    fn recorder() -> FrameRecorder<bool> {
+        let mut recorder = FrameRecorder::new();
+        for step in 0..3 {
+            let grid = Grid::from_function(Coord(3, 1), |c| c.0 == step);
+            recorder.record_with_caption(&grid, format!("step {}", step));
+        }
+        recorder
+    }
+
+    #[test]
+    fn test_render() {
+        let recorder = recorder();
+        assert_eq!(recorder.render_frame(1, style, false), ".#.\nstep 1\n");
+        assert_eq!(recorder.render_frame(0, style, true), "\x1b[38;2;255;0;0m#\x1b[0m..\nstep 0\n");
+        let mut out = Vec::new();
+        recorder.write_frames(&mut out, style).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("=== Frame 1 of 3 ===\n#..\nstep 0\n"));
+        assert_eq!(text.lines().count(), 9);
+    }
+
+    #[test]
+    fn test_playback_state() {
+        let options = PlaybackOptions::default();
+        let mut state = PlaybackState::new(3, &options);
+        state.tick();
+        assert_eq!(state.index, 1);
+        state.tick();
+        assert_eq!((state.index, state.paused), (2, true)); // stops at the end
+        assert!(state.apply(Command::StepBack));
+        assert!(state.apply(Command::StepBack));
+        assert!(state.apply(Command::StepBack));
+        assert_eq!(state.index, 0);
+        state.tick();
+        assert_eq!(state.index, 0); // still paused
+        assert!(state.apply(Command::parse("+").unwrap()));
+        assert_eq!(state.delay, Duration::from_millis(50));
+        assert_eq!(Command::parse(""), Some(Command::TogglePause));
+        assert_eq!(Command::parse("x"), None);
+        assert!(!state.apply(Command::Quit));
+    }
+
+    #[test]
+    fn test_stdin_closed() {
+        let options = PlaybackOptions::default();
+        let (sender, receiver) = mpsc::channel();
+        sender.send(Command::Faster).unwrap();
+        drop(sender);
+        let mut state = PlaybackState::new(3, &options);
+        assert!(state.apply_pending(&receiver)); // still playing, so keep going
+        assert_eq!(state.delay, Duration::from_millis(50));
+        state.tick();
+        state.tick();
+        assert!(!state.apply_pending(&receiver)); // paused on the last frame
+        assert_eq!(state.index, 2);
+
+        let (sender, receiver) = mpsc::channel::<Command>();
+        drop(sender);
+        let paused = PlaybackOptions{start_paused: true, ..PlaybackOptions::default()};
+        assert!(!PlaybackState::new(3, &paused).apply_pending(&receiver));
+    }
+}
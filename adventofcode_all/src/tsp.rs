@@ -0,0 +1,300 @@
+//! Puzzles keep asking for the best order in which to visit a bunch of places (the
+//! traveling salesman problem). Trying every permutation works for 8 places but not for
+//! 15, so this module solves it with the Held-Karp algorithm: dynamic programming over
+//! (set of places visited, place we ended at), which is O(2^n * n^2).
+
+use std::fmt::{Display, Formatter};
+use std::ops::Add;
+use num::Zero;
+use crate::grid::{Coord, Grid, Neighborhood};
+
+
+/// Whether we are looking for the cheapest or the most expensive route.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Objective {
+    Min,
+    Max,
+}
+
+/// Contains the distances between a set of nodes. The nodes are identified by integers
+/// starting from 0. The distance from a to b need not be the same as from b to a.
+#[derive(Debug, Clone)]
+pub struct DistanceMatrix<C> {
+    size: usize,
+    dist: Vec<C>, // there are size*size elements
+}
+
+/// A route through the nodes, and its total cost. For a cycle, the first node is repeated
+/// at the end.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Tour<C> {
+    pub cost: C,
+    pub nodes: Vec<usize>,
+}
+
+
+impl Objective {
+    /// Returns true if a is strictly better than b.
+    fn is_better<C: Ord>(&self, a: &C, b: &C) -> bool {
+        match self {
+            Objective::Min => a < b,
+            Objective::Max => a > b,
+        }
+    }
+}
+
+
+impl<C: Copy + Zero> DistanceMatrix<C> {
+    /// Returns a new DistanceMatrix of the given size with all distances set to zero.
+    pub fn new_zeros(size: usize) -> Self {
+        DistanceMatrix{size, dist: vec![C::zero(); size * size]}
+    }
+
+    /// Returns a new DistanceMatrix of the given size where the distance from a to b is
+    /// f(a, b).
+    pub fn from_function<F>(size: usize, mut f: F) -> Self
+        where F: FnMut(usize, usize) -> C
+    {
+        let dist = (0..size * size).map(|i| f(i / size, i % size)).collect();
+        DistanceMatrix{size, dist}
+    }
+
+    /// Returns the number of nodes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the distance from n1 to n2. Panics if either is not a node in this.
+    pub fn dist(&self, n1: usize, n2: usize) -> C {
+        assert!(n1 < self.size && n2 < self.size);
+        self.dist[n1 * self.size + n2]
+    }
+
+    /// Sets the distance from n1 to n2 (but not the distance from n2 to n1).
+    pub fn set_dist(&mut self, n1: usize, n2: usize, d: C) {
+        assert!(n1 < self.size && n2 < self.size);
+        self.dist[n1 * self.size + n2] = d;
+    }
+
+    /// Sets the distance between n1 and n2 in both directions.
+    pub fn set_symmetric(&mut self, n1: usize, n2: usize, d: C) {
+        self.set_dist(n1, n2, d);
+        self.set_dist(n2, n1, d);
+    }
+}
+
+
+impl DistanceMatrix<usize> {
+    /// Builds the matrix of shortest-path distances between the given points on a Grid,
+    /// where node i is points[i]. The rules for moving are the same as for
+    /// Grid::shortest_path(). Returns None if any point can't reach some other point.
+    pub fn from_grid<T, P, C>(
+        grid: &Grid<T>,
+        points: &[Coord],
+        neighborhood: Neighborhood,
+        passable: P,
+        cost: C,
+    ) -> Option<Self>
+        where P: Fn(&T, &T) -> bool, C: Fn(&T) -> usize
+    {
+        let mut answer = Self::new_zeros(points.len());
+        for (i, start) in points.iter().enumerate() {
+            let dists = grid.distances_from(*start, neighborhood, &passable, &cost);
+            for (j, end) in points.iter().enumerate() {
+                answer.set_dist(i, j, (*dists.get(*end))?);
+            }
+        }
+        Some(answer)
+    }
+}
+
+
+impl<C: Display> Display for Tour<C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let path: Vec<String> = self.nodes.iter().map(|n| n.to_string()).collect();
+        write!(f, "{} (cost {})", path.join("->"), self.cost)
+    }
+}
+
+
+/// Finds the best path that visits every node exactly once (without returning to where it
+/// started). If start is Some then the path must begin at that node; otherwise it may
+/// begin anywhere. Returns None only if there are no nodes. Panics if start is not a node.
+pub fn best_path<C>(dists: &DistanceMatrix<C>, objective: Objective, start: Option<usize>) -> Option<Tour<C>>
+    where C: Copy + Ord + Zero + Add<Output=C>
+{
+    let starts: Vec<usize> = match start {
+        Some(s) => vec![s],
+        None => (0..dists.size()).collect(),
+    };
+    let (table, full) = held_karp(dists, objective, &starts)?;
+    let last = (0..dists.size())
+        .filter(|n| table.cost(full, *n).is_some())
+        .reduce(|a, b| if objective.is_better(&table.cost(full, b).unwrap(), &table.cost(full, a).unwrap()) {b} else {a})?;
+    let cost = table.cost(full, last).unwrap();
+    Some(Tour{cost, nodes: table.path_to(full, last)})
+}
+
+/// Finds the best cycle that visits every node exactly once and then returns to the
+/// starting node. The tour returned starts (and ends) at node 0. Returns None only if
+/// there are no nodes.
+pub fn best_cycle<C>(dists: &DistanceMatrix<C>, objective: Objective) -> Option<Tour<C>>
+    where C: Copy + Ord + Zero + Add<Output=C>
+{
+    let (table, full) = held_karp(dists, objective, &[0])?;
+    let mut best: Option<(C, usize)> = None;
+    for last in 0..dists.size() {
+        if let Some(cost) = table.cost(full, last) {
+            let cycle_cost = cost + dists.dist(last, 0);
+            if best.is_none_or(|(best_cost, _)| objective.is_better(&cycle_cost, &best_cost)) {
+                best = Some((cycle_cost, last));
+            }
+        }
+    }
+    let (cost, last) = best?;
+    let mut nodes = table.path_to(full, last);
+    nodes.push(0);
+    Some(Tour{cost, nodes})
+}
+
+
+/// The table built by held_karp(). For each set of visited nodes (as a bitmask) and each
+/// node the path ended at, it holds the best cost and the node visited just before that.
+struct HeldKarpTable<C> {
+    size: usize,
+    entries: Vec<Option<(C, usize)>>, // indexed by mask * size + last
+}
+
+impl<C: Copy> HeldKarpTable<C> {
+    /// Returns the best cost of a path visiting mask and ending at last, if there is one.
+    fn cost(&self, mask: usize, last: usize) -> Option<C> {
+        self.entries[mask * self.size + last].map(|(cost, _)| cost)
+    }
+
+    /// Walks back through the table to find the nodes of the best path visiting mask and
+    /// ending at last.
+    fn path_to(&self, mut mask: usize, mut last: usize) -> Vec<usize> {
+        let mut nodes = Vec::with_capacity(self.size);
+        loop {
+            nodes.push(last);
+            let (_, prev) = self.entries[mask * self.size + last].unwrap();
+            mask &= !(1 << last);
+            if mask == 0 {
+                break;
+            }
+            last = prev;
+        }
+        nodes.reverse();
+        nodes
+    }
+}
+
+/// Fills in the Held-Karp table for paths that begin at one of the starts. Returns the
+/// table and the mask meaning "all nodes", or None if there are no nodes.
+fn held_karp<C>(dists: &DistanceMatrix<C>, objective: Objective, starts: &[usize]) -> Option<(HeldKarpTable<C>, usize)>
+    where C: Copy + Ord + Zero + Add<Output=C>
+{
+    let size = dists.size();
+    if size == 0 {
+        return None;
+    }
+    assert!(size < usize::BITS as usize, "too many nodes for Held-Karp");
+    for start in starts {
+        assert!(*start < size, "start node {} is out of range for {} nodes", start, size);
+    }
+    let full = (1 << size) - 1;
+    let mut table = HeldKarpTable{size, entries: vec![None; (full + 1) * size]};
+    for start in starts {
+        table.entries[(1 << start) * size + start] = Some((C::zero(), *start));
+    }
+    // every mask is larger than the masks it was built from, so go in increasing order
+    for mask in 1..=full {
+        for last in 0..size {
+            if let Some((cost, _)) = table.entries[mask * size + last] {
+                for next in 0..size {
+                    if mask & (1 << next) == 0 {
+                        let next_mask = mask | (1 << next);
+                        let next_cost = cost + dists.dist(last, next);
+                        let entry = &mut table.entries[next_mask * size + next];
+                        if entry.is_none_or(|(old_cost, _)| objective.is_better(&next_cost, &old_cost)) {
+                            *entry = Some((next_cost, last));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Some((table, full))
+}
+
+
+
+// ========================== TESTS ==========================
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The cities from Advent of Code 2015 day 9.
+    fn cities() -> DistanceMatrix<usize> {
+        let mut dists = DistanceMatrix::new_zeros(3);
+        dists.set_symmetric(0, 1, 464); // London to Dublin
+        dists.set_symmetric(0, 2, 518); // London to Belfast
+        dists.set_symmetric(1, 2, 141); // Dublin to Belfast
+        dists
+    }
+
+    #[test]
+    fn test_open_path() {
+        let shortest = best_path(&cities(), Objective::Min, None).unwrap();
+        assert_eq!(shortest.cost, 605);
+        assert_eq!(shortest.nodes.len(), 3);
+        let longest = best_path(&cities(), Objective::Max, None).unwrap();
+        assert_eq!(longest.cost, 982);
+        let from_dublin = best_path(&cities(), Objective::Min, Some(1)).unwrap();
+        assert_eq!(from_dublin.cost, 141 + 518);
+        assert_eq!(from_dublin.nodes, vec![1, 2, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "start node 3 is out of range for 3 nodes")]
+    fn test_start_out_of_range() {
+        best_path(&cities(), Objective::Min, Some(3));
+    }
+
+    #[test]
+    fn test_cycle_max() {
+        // The seating happiness from Advent of Code 2015 day 13 (Alice, Bob, Carol, David).
+        let gain = [
+            [0, 54, -79, -2],
+            [83, 0, -7, -63],
+            [-62, 60, 0, 55],
+            [46, -7, 41, 0],
+        ];
+        let dists: DistanceMatrix<i32> = DistanceMatrix::from_function(4, |a, b| gain[a][b] + gain[b][a]);
+        let tour = best_cycle(&dists, Objective::Max).unwrap();
+        assert_eq!(tour.cost, 330);
+        assert_eq!(tour.nodes.len(), 5);
+        assert_eq!(tour.nodes.first(), tour.nodes.last());
+    }
+
+    #[test]
+    fn test_from_grid() {
+        // The example from Advent of Code 2016 day 24.
+        let rows = [
+            "###########",
+            "#0.1.....2#",
+            "#.#######.#",
+            "#4.......3#",
+            "###########",
+        ];
+        let grid: Grid<char> = Grid::from_function(Coord(11, 5), |c| rows[c.1].as_bytes()[c.0] as char);
+        let points: Vec<Coord> = ['0', '1', '2', '3', '4'].iter()
+            .map(|ch| grid.bound().range_by_rows().find(|c| grid.get(*c) == ch).unwrap())
+            .collect();
+        let dists = DistanceMatrix::from_grid(&grid, &points, Neighborhood::Four, |_, to| *to != '#', |_| 1).unwrap();
+        assert_eq!(dists.dist(0, 4), 2);
+        assert_eq!(best_path(&dists, Objective::Min, Some(0)).unwrap().cost, 14);
+        assert_eq!(best_cycle(&dists, Objective::Min).unwrap().cost, 20);
+    }
+}